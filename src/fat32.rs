@@ -0,0 +1,257 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error as StdError;
+
+#[cfg(feature = "std")]
+use std::path::Path as StdPath;
+
+use crate::{cp437, Fat16BPB, Fat16DirEntry, FatType, ParseResult, Path};
+
+// FAT32 ボリューム (読み込みのみ)
+#[derive(Debug)]
+pub struct Fat32 {
+    pub bpb: Fat16BPB,
+    pub ebpb: Fat32EBPB,
+    pub alloc_table: Fat32AllocTable,
+    pub root_dir: Vec<Fat16DirEntry>,
+    // データ領域
+    pub clusters: Vec<u8>,
+}
+
+impl Fat32 {
+    #[cfg(feature = "std")]
+    pub fn new<P: AsRef<StdPath>>(path: P) -> Result<Fat32, Box<dyn StdError>> {
+        Fat32::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Fat32, Box<dyn StdError>> {
+        // BPB は FAT12/16 と共通、その後ろに FAT32 用の拡張 BPB が続く
+        let image = &bytes;
+        let (bpb, rest) = Fat16BPB::parse(image)?;
+        let (ebpb, _) = Fat32EBPB::parse(rest)?;
+
+        // 予約領域を読み飛ばして FAT をパース
+        let reserved_size = bpb.reserved_sector_count as usize * bpb.bytes_per_sector as usize;
+        if bpb.reserved_sector_count == 0 || reserved_size > image.len() {
+            return Err(format!("Invalid reserved sector count: {}", bpb.reserved_sector_count).into());
+        }
+        let (alloc_table, rest) = Fat32AllocTable::parse(&image[reserved_size..], &bpb, &ebpb)?;
+
+        // FAT32 にはルートディレクトリ領域がなく、FAT の直後からデータ領域が始まる
+        let mut fs = Fat32 {
+            bpb,
+            ebpb,
+            alloc_table,
+            root_dir: vec![],
+            clusters: rest.to_vec(),
+        };
+
+        // ルートディレクトリは root_cluster から始まるクラスタチェーン
+        fs.root_dir = fs.read_dir_cluster(fs.ebpb.root_cluster)?;
+
+        Ok(fs)
+    }
+
+    pub fn read_file(&self, path: &Path) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // path にマッチする DirEntry を探す
+        let entry = self.find_dir_entry(path)?;
+        self.read_entry(&entry)
+    }
+
+    pub fn read_entry(&self, entry: &Fat16DirEntry) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // クラスタを辿ってデータを取得 (空のファイルはクラスタを持たない)
+        let mut file = self.read_clusters(entry.first_cluster)?;
+        file.truncate(entry.file_size as usize);
+        Ok(file)
+    }
+
+    pub fn read_directory(&self, path: &Path) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        // ルートディレクトリは DirEntry を持たないのでそのまま返す
+        if path.is_root() {
+            return Ok(self.root_dir.clone());
+        }
+
+        let entry = self.find_dir_entry(path)?;
+        if !entry.is_dir() {
+            return Err(format!("Not a directory: '{}'", path.original()).into());
+        }
+        self.read_dir_cluster(entry.first_cluster)
+    }
+
+    pub fn bytes_per_cluster(&self) -> usize {
+        self.bpb.bytes_per_sector as usize * self.bpb.sectors_per_cluster as usize
+    }
+
+    pub fn data_region_offset(&self) -> usize {
+        // 予約領域 + FAT 領域 (ルートディレクトリ領域はない)
+        let sectors = self.bpb.reserved_sector_count as usize + self.bpb.num_fats as usize * self.ebpb.sectors_per_fat_32 as usize;
+        sectors * self.bpb.bytes_per_sector as usize
+    }
+
+    pub fn cluster_count(&self) -> u32 {
+        let data_sectors = self.bpb.large_sectors.saturating_sub((self.data_region_offset() / self.bpb.bytes_per_sector as usize) as u32);
+        data_sectors / self.bpb.sectors_per_cluster as u32
+    }
+
+    pub fn fs_type(&self) -> String {
+        // "FAT32   " などの拡張 BPB のファイルシステム種別
+        cp437::decode(&self.ebpb.file_system_type).trim_end_matches(' ').to_string()
+    }
+
+    fn find_dir_entry(&self, path: &Path) -> Result<Fat16DirEntry, Box<dyn StdError>> {
+        // path の各要素を順に辿る (ルートディレクトリは複製せずに借用する)
+        let mut entries: Cow<'_, [Fat16DirEntry]> = Cow::Borrowed(&self.root_dir);
+        let mut found: Option<Fat16DirEntry> = None;
+        for name in path.parse() {
+            if let Some(entry) = found.take() {
+                entries = Cow::Owned(self.read_dir_cluster(entry.first_cluster)?);
+            }
+            found = Some(
+                entries
+                    .iter()
+                    .find(|e| e.matches(name))
+                    .ok_or("No such file or direcotry")?
+                    .clone(),
+            );
+        }
+        Ok(found.ok_or("Root directory has no directory entry")?)
+    }
+
+    fn read_dir_cluster(&self, first_cluster: u32) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        // クラスタ境界をまたぐ LFN もあるので、まとめて 1 度にパースする
+        let dir_bytes = self.read_clusters(first_cluster)?;
+        let (dirs, _) = Fat16DirEntry::parses(&dir_bytes, (dir_bytes.len() / 32) as u16)?;
+        Ok(dirs)
+    }
+
+    fn read_clusters(&self, first_cluster: u32) -> Result<Vec<u8>, Box<dyn StdError>> {
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let mut data = Vec::new();
+        for cluster_number in self.alloc_table.get_cluster_chain(first_cluster) {
+            let head = (cluster_number as usize - 2) * bytes_per_cluster;
+            let cluster_data = self.clusters
+                .get(head..head + bytes_per_cluster)
+                .ok_or_else(|| format!("Cluster number out of range. cluster = {}", cluster_number))?;
+            data.extend_from_slice(cluster_data);
+        }
+        Ok(data)
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Fat32EBPB {
+    // Sectors per FAT (4bytes)
+    pub sectors_per_fat_32: u32,
+    // Extended Flags (2bytes)
+    pub ext_flags: u16,
+    // File System Version (2bytes)
+    pub fs_version: u16,
+    // Root Directory Cluster (4bytes)
+    pub root_cluster: u32,
+    // FSInfo Sector (2bytes)
+    pub fs_info: u16,
+    // Backup Boot Sector (2bytes)
+    pub backup_boot_sector: u16,
+    // Reserved (12bytes)
+    pub reserved: [u8; 12],
+    // Drive Number (1byte)
+    pub drive_number: u8,
+    // Reserved1 (1byte)
+    pub reserved1: u8,
+    // Boot Signature (1byte)
+    pub boot_signature: u8,
+    // Volume ID (4bytes)
+    pub volume_id: u32,
+    // Volume Label (11bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialize_hex"))]
+    pub volume_label: [u8; 11],
+    // File System Type (8bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialize_hex"))]
+    pub file_system_type: [u8; 8],
+    // Boot Code (420bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialize_hex"))]
+    pub boot_code: [u8; 420],
+    // Boot Partition Signature (2bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serialize_hex"))]
+    pub boot_partition_signature: [u8; 2],
+}
+
+impl Fat32EBPB {
+    pub fn parse(bytes: &[u8]) -> ParseResult<'_, Fat32EBPB> {
+        if bytes.len() < 476 {
+            return Err(format!("EBPB too small: need at least 476 bytes, got {}", bytes.len()).into());
+        }
+
+        let ebpb = Fat32EBPB {
+            sectors_per_fat_32: u32::from_le_bytes(bytes[0..4].try_into()?),
+            ext_flags: u16::from_le_bytes(bytes[4..6].try_into()?),
+            fs_version: u16::from_le_bytes(bytes[6..8].try_into()?),
+            root_cluster: u32::from_le_bytes(bytes[8..12].try_into()?),
+            fs_info: u16::from_le_bytes(bytes[12..14].try_into()?),
+            backup_boot_sector: u16::from_le_bytes(bytes[14..16].try_into()?),
+            reserved: bytes[16..28].try_into()?,
+            drive_number: bytes[28],
+            reserved1: bytes[29],
+            boot_signature: bytes[30],
+            volume_id: u32::from_le_bytes(bytes[31..35].try_into()?),
+            volume_label: bytes[35..46].try_into()?,
+            file_system_type: bytes[46..54].try_into()?,
+            boot_code: bytes[54..474].try_into()?,
+            boot_partition_signature: bytes[474..476].try_into()?,
+        };
+        Ok((ebpb, &bytes[476..]))
+    }
+}
+
+#[derive(Debug)]
+pub struct Fat32AllocTable {
+    // 上位 4 ビットは予約なので取り除いた値
+    table: Vec<u32>,
+}
+
+impl Fat32AllocTable {
+    pub fn parse<'a>(bytes: &'a [u8], bpb: &Fat16BPB, ebpb: &Fat32EBPB) -> ParseResult<'a, Fat32AllocTable> {
+        let fat_bytes = ebpb.sectors_per_fat_32 as usize * bpb.bytes_per_sector as usize;
+        let fat_size = bpb.num_fats as usize * fat_bytes;
+        if fat_bytes == 0 {
+            return Err("Invalid sectors_per_fat_32: 0".into());
+        }
+        if bytes.len() < fat_size {
+            return Err(format!("FAT region is truncated. expected = {} bytes, len = {}", fat_size, bytes.len()).into());
+        }
+
+        // 1 つ目の FAT のみ読み込む
+        let table = bytes[..fat_bytes]
+            .chunks_exact(4)
+            .map(|entry| u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]) & 0x0FFF_FFFF)
+            .collect();
+
+        Ok((Fat32AllocTable { table }, &bytes[fat_size..]))
+    }
+
+    pub fn entry(&self, cluster: u32) -> Option<u32> {
+        // FAT エントリの値 (範囲外は None)
+        self.table.get(cluster as usize).copied()
+    }
+
+    pub fn get_cluster_chain(&self, start_cluster: u32) -> Vec<u32> {
+        // 先頭クラスタ 0 は空のファイル・ディレクトリ (チェーンを持たない)
+        let mut chain = vec![];
+        let mut cluster = start_cluster;
+
+        // 範囲外や循環したチェーンで止まらなくならないよう、エントリ数で打ち切る
+        while cluster >= 2 && cluster < FatType::Fat32.eoc_marker() && chain.len() < self.table.len() {
+            chain.push(cluster);
+            match self.entry(cluster) {
+                Some(next) => cluster = next,
+                None => break,
+            }
+        }
+        chain
+    }
+}
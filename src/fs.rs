@@ -0,0 +1,81 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error as StdError;
+
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use crate::{Fat16, Fat16DirEntry, Path};
+
+// std::fs と同じ形の関数 (第 1 引数にファイルシステムを取る)
+
+pub fn read<P: Into<Path>>(fs: &Fat16, path: P) -> Result<Vec<u8>, Box<dyn StdError>> {
+    fs.read_file(&path.into())
+}
+
+pub fn read_to_string<P: Into<Path>>(fs: &Fat16, path: P) -> Result<String, Box<dyn StdError>> {
+    Ok(String::from_utf8(read(fs, path)?)?)
+}
+
+pub fn read_dir<P: Into<Path>>(fs: &Fat16, path: P) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+    // std::fs::read_dir と同様に . と .. は含めない
+    let path = path.into();
+    if !fs.is_dir(&path)? {
+        return Err(format!("Not a directory: {}", path.original()).into());
+    }
+    let entries = fs.read_directory(&path)?
+        .into_iter()
+        .filter(|entry| !entry.is_dot())
+        .collect();
+    Ok(entries)
+}
+
+pub fn metadata<P: Into<Path>>(fs: &Fat16, path: P) -> Result<Metadata, Box<dyn StdError>> {
+    // ルートディレクトリは DirEntry を持たない
+    let path = path.into();
+    if path.is_root() {
+        return Ok(Metadata { entry: None });
+    }
+    let entry = fs.resolve_chain(&path)?.pop();
+    Ok(Metadata { entry })
+}
+
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    // ルートディレクトリの場合は None
+    entry: Option<Fat16DirEntry>,
+}
+
+impl Metadata {
+    pub fn is_dir(&self) -> bool {
+        self.entry.as_ref().is_none_or(|entry| entry.is_dir())
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.entry.as_ref().is_some_and(|entry| !entry.is_dir() && !entry.is_volume_label())
+    }
+
+    pub fn len(&self) -> u64 {
+        self.entry.as_ref().map_or(0, |entry| entry.file_size as u64)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn readonly(&self) -> bool {
+        self.entry.as_ref().is_some_and(|entry| entry.attribute & 0x01 != 0)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn modified(&self) -> Result<SystemTime, Box<dyn StdError>> {
+        let entry = self.entry.as_ref().ok_or("Root directory has no timestamp")?;
+        Ok(entry.last_modify_date.to_system_time(&entry.last_modify_time))
+    }
+
+    pub fn entry(&self) -> Option<&Fat16DirEntry> {
+        self.entry.as_ref()
+    }
+}
@@ -0,0 +1,140 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error as StdError;
+
+// 8.3 形式の名前を書き込む際の禁止文字
+const SFN_INVALID_CHARS: &str = "+,;=[]";
+// LFN の禁止文字
+const LFN_INVALID_CHARS: &str = "\\/:*?\"<>|";
+
+pub fn validate_name(name: &str) -> Result<(), Box<dyn StdError>> {
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(format!("Invalid file name: '{}'", name).into());
+    }
+    if name.encode_utf16().count() > 255 {
+        return Err(format!("File name too long: '{}'", name).into());
+    }
+    if name.chars().any(|c| c.is_control() || LFN_INVALID_CHARS.contains(c)) {
+        return Err(format!("Invalid character in file name: '{}'", name).into());
+    }
+    Ok(())
+}
+
+pub fn generate_short_name(long_name: &str, existing: &[[u8; 11]]) -> [u8; 11] {
+    // 大文字化し、8.3 形式で使えない文字を '_' に置き換える
+    let convert = |part: &str| -> (Vec<u8>, bool) {
+        let mut lossy = false;
+        let mut converted = vec![];
+        for c in part.chars() {
+            match c {
+                ' ' | '.' => lossy = true,
+                c if c.is_ascii() && !SFN_INVALID_CHARS.contains(c) => converted.push(c.to_ascii_uppercase() as u8),
+                _ => {
+                    lossy = true;
+                    converted.push(b'_');
+                }
+            }
+        }
+        (converted, lossy)
+    };
+
+    // 最後の '.' で本体と拡張子に分ける (先頭の '.' は無視)
+    let trimmed = long_name.trim_start_matches('.');
+    let (base, ext) = match trimmed.rfind('.') {
+        Some(idx) => (&trimmed[..idx], &trimmed[idx + 1..]),
+        None => (trimmed, ""),
+    };
+    let (mut base, base_lossy) = convert(base);
+    let (mut ext, ext_lossy) = convert(ext);
+    let lossy = base_lossy || ext_lossy || base.len() > 8 || ext.len() > 3 || trimmed.len() != long_name.len();
+    if base.is_empty() {
+        base.push(b'_');
+    }
+    ext.truncate(3);
+
+    let build = |base: &[u8]| -> [u8; 11] {
+        let mut short_name = [b' '; 11];
+        short_name[..base.len()].copy_from_slice(base);
+        short_name[8..8 + ext.len()].copy_from_slice(&ext);
+        short_name
+    };
+
+    // そのまま 8.3 形式で表せて衝突しないならそのまま使う
+    if !lossy {
+        let short_name = build(&base);
+        if !existing.contains(&short_name) {
+            return short_name;
+        }
+    }
+
+    // "NAME~N.EXT" の形式で衝突しない番号を探す
+    for n in 1..=999999u32 {
+        let tail = format!("~{}", n);
+        let mut numbered = base.clone();
+        numbered.truncate(8 - tail.len());
+        numbered.extend(tail.as_bytes());
+        let short_name = build(&numbered);
+        if !existing.contains(&short_name) {
+            return short_name;
+        }
+    }
+    unreachable!("no free short name for '{}'", long_name)
+}
+
+pub fn short_names_in(stream: &[u8]) -> Vec<[u8; 11]> {
+    // 有効な SFN エントリの 8.3 形式の名前を集める
+    stream
+        .chunks(32)
+        .filter(|record| record.len() == 32 && record[0] != 0x00 && record[0] != 0xE5 && record[11] != 0x0f)
+        .map(|record| record[0..11].try_into().unwrap())
+        .collect()
+}
+
+pub fn needs_lfn(long_name: &str, short_name: &[u8; 11]) -> bool {
+    // 8.3 形式の名前で元の名前を表せない場合は LFN が必要
+    let base = String::from_utf8_lossy(&short_name[0..8]).trim_end().to_string();
+    let ext = String::from_utf8_lossy(&short_name[8..11]).trim_end().to_string();
+    let display = if ext.is_empty() { base } else { format!("{}.{}", base, ext) };
+    display != long_name
+}
+
+pub fn checksum(short_name: &[u8; 11]) -> u8 {
+    short_name
+        .iter()
+        .fold(0u8, |sum, b| ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(*b))
+}
+
+pub fn encode(long_name: &str, short_name: &[u8; 11]) -> Vec<[u8; 32]> {
+    let checksum = checksum(short_name);
+
+    // UTF-16 に変換し、13 文字単位になるようヌル終端と 0xFFFF で埋める
+    let mut units = long_name.encode_utf16().collect::<Vec<_>>();
+    if units.len() % 13 != 0 {
+        units.push(0x0000);
+    }
+    while units.len() % 13 != 0 {
+        units.push(0xFFFF);
+    }
+
+    // ディスク上では最後の断片から順に並ぶ
+    let count = units.len() / 13;
+    let mut records = vec![];
+    for seq in (1..=count).rev() {
+        let mut record = [0u8; 32];
+        record[0] = seq as u8 | if seq == count { 0x40 } else { 0x00 };
+        record[11] = 0x0f;
+        record[13] = checksum;
+
+        let chunk = &units[(seq - 1) * 13..seq * 13];
+        let offsets = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+        for (unit, offset) in chunk.iter().zip(offsets) {
+            record[offset..offset + 2].copy_from_slice(&unit.to_le_bytes());
+        }
+        records.push(record);
+    }
+
+    records
+}
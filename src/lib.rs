@@ -1,135 +1,2326 @@
-use std::error::Error as StdError;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::error::Error as StdError;
+use core::fmt::Display;
+use core::time::Duration;
+
+#[cfg(feature = "std")]
 use std::fs::File;
+#[cfg(feature = "std")]
 use std::path::Path as StdPath;
-use std::io::Read;
-use std::fmt::Display;
+#[cfg(feature = "std")]
+use std::io::{Read, Seek, SeekFrom};
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "sha2")]
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "flate2")]
+use flate2::read::GzDecoder;
+
+mod cp437;
+pub mod fat32;
+pub mod fs;
+pub mod lfn;
+
+// パース結果と残りのバイト列
+pub type ParseResult<'a, T> = Result<(T, &'a [u8]), Box<dyn StdError>>;
+
+// 共有されているクラスタと、それを参照するパスの一覧
+pub type CrossLink = (u16, Vec<String>);
 
 #[derive(Debug)]
+pub enum Fat16Error {
+    // ディレクトリのクラスタチェーンがイメージの範囲外を指している
+    DirectoryChainBroken { path: String, cluster: u16 },
+    // ファイル・ディレクトリのクラスタチェーンが壊れている
+    BrokenChain { path: String, error: ChainError },
+    // file_size から求めたクラスタ数とチェーンの長さが一致しない
+    FileSizeMismatch { path: String, file_size: u32, expected_clusters: usize, chain_clusters: usize },
+}
+
+impl Display for Fat16Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Fat16Error::DirectoryChainBroken { path, cluster } => {
+                write!(f, "Directory '{}' has a broken cluster chain at cluster {}", path, cluster)
+            }
+            Fat16Error::BrokenChain { path, error } => {
+                write!(f, "Cluster chain of '{}' is broken: {}", path, error)
+            }
+            Fat16Error::FileSizeMismatch { path, file_size, expected_clusters, chain_clusters } => {
+                write!(
+                    f,
+                    "File size of '{}' ({} bytes) needs {} clusters, but its chain has {}",
+                    path, file_size, expected_clusters, chain_clusters,
+                )
+            }
+        }
+    }
+}
+
+impl StdError for Fat16Error {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    // 同じクラスタを 2 回辿った
+    Cycle(u16),
+    // FAT・データ領域の範囲外のクラスタ
+    OutOfRange(u16),
+    // 不良クラスタ (0xFFF7) がチェーンに含まれている
+    BadCluster(u16),
+    // 未使用クラスタ (0x0000) がチェーンに含まれている
+    FreeCluster(u16),
+}
+
+impl Display for ChainError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ChainError::Cycle(cluster) => write!(f, "cycle detected at cluster {}", cluster),
+            ChainError::OutOfRange(cluster) => write!(f, "cluster {} is out of range", cluster),
+            ChainError::BadCluster(cluster) => write!(f, "cluster {} is marked as bad", cluster),
+            ChainError::FreeCluster(cluster) => write!(f, "cluster {} is marked as free", cluster),
+        }
+    }
+}
+
+impl StdError for ChainError {}
+
+#[derive(Debug, Clone)]
 pub struct Path {
+    // 比較用 (小文字化済み)
     abs_path: String,
+    // 表示用 (入力されたままの大文字・小文字)
+    orig_path: String,
 }
 
 impl From<&str> for Path {
     fn from(s: &str) -> Path {
-        Path { abs_path: s.to_string().to_ascii_lowercase() }
+        // 末尾の '/' を取り除き、空のパスはルートとみなす
+        let trimmed = s.trim_end_matches('/');
+        let normalized = if trimmed.starts_with('/') {
+            trimmed.to_string()
+        } else {
+            format!("/{}", trimmed)
+        };
+        Path {
+            abs_path: normalized.to_ascii_lowercase(),
+            orig_path: normalized,
+        }
+    }
+}
+
+impl From<String> for Path {
+    fn from(s: String) -> Path {
+        Path::from(s.as_str())
+    }
+}
+
+impl From<&String> for Path {
+    fn from(s: &String) -> Path {
+        Path::from(s.as_str())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<&StdPath> for Path {
+    fn from(path: &StdPath) -> Path {
+        // 区切り文字は '/' にそろえる (Windows の '\' も含む)
+        Path::from(path.to_string_lossy().replace('\\', "/").as_str())
     }
 }
 
 impl Path {
     pub fn parse(&self) -> Vec<&str> {
-        self.abs_path[1..].split('/').into_iter().collect()
+        // ルートは要素を持たない
+        if self.is_root() {
+            return vec![];
+        }
+        self.abs_path[1..].split('/').collect()
+    }
+
+    pub fn original(&self) -> &str {
+        &self.orig_path
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.abs_path == "/"
+    }
+
+    pub fn file_name(&self) -> &str {
+        // 最後の要素 (入力されたままの大文字・小文字)
+        self.orig_path.rsplit('/').next().unwrap_or("")
+    }
+
+    pub fn parent(&self) -> Path {
+        match self.orig_path.rfind('/') {
+            Some(0) | None => Path::from("/"),
+            Some(idx) => Path::from(&self.orig_path[..idx]),
+        }
+    }
+}
+
+// セクタ単位で読み書きするブロックデバイス
+// 組み込み環境ではハードウェアや RAM 領域に対して独自に実装する
+// Fat16 をスレッド間で受け渡せるように Send を要求する
+pub trait BlockDevice: Send {
+    fn read_sector(&self, lba: u64, buf: &mut [u8]) -> Result<(), Box<dyn StdError>>;
+
+    fn write_sector(&self, lba: u64, _buf: &[u8]) -> Result<(), Box<dyn StdError>> {
+        Err(format!("Block device is read-only. lba = {}", lba).into())
+    }
+
+    // 総セクタ数
+    fn sector_count(&self) -> u64;
+
+    fn sector_size(&self) -> usize {
+        512
+    }
+}
+
+impl BlockDevice for Vec<u8> {
+    fn read_sector(&self, lba: u64, buf: &mut [u8]) -> Result<(), Box<dyn StdError>> {
+        let head = lba as usize * self.sector_size();
+        let sector = self
+            .get(head..head + buf.len())
+            .ok_or(format!("Sector out of range. lba = {}", lba))?;
+        buf.copy_from_slice(sector);
+        Ok(())
+    }
+
+    fn sector_count(&self) -> u64 {
+        (self.len() / self.sector_size()) as u64
+    }
+}
+
+// Read + Seek を実装した型 (File など) をブロックデバイスとして扱う
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct IoDevice<T> {
+    inner: RefCell<T>,
+}
+
+#[cfg(feature = "std")]
+impl<T: Read + Seek> IoDevice<T> {
+    pub fn new(inner: T) -> IoDevice<T> {
+        IoDevice { inner: RefCell::new(inner) }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Read + Seek + Send> BlockDevice for IoDevice<T> {
+    fn read_sector(&self, lba: u64, buf: &mut [u8]) -> Result<(), Box<dyn StdError>> {
+        let mut inner = self.inner.borrow_mut();
+        inner.seek(SeekFrom::Start(lba * self.sector_size() as u64))?;
+        inner.read_exact(buf)?;
+        Ok(())
+    }
+
+    fn sector_count(&self) -> u64 {
+        let len = self.inner.borrow_mut().seek(SeekFrom::End(0)).unwrap_or(0);
+        len / self.sector_size() as u64
+    }
+}
+
+// データ領域をオンデマンドで読み込むためのリーダ
+struct ClusterReader {
+    device: Box<dyn BlockDevice>,
+    // イメージ先頭からデータ領域までのオフセット
+    data_offset: u64,
+}
+
+impl core::fmt::Debug for ClusterReader {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ClusterReader")
+            .field("data_offset", &self.data_offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClusterReader {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> Result<(), Box<dyn StdError>> {
+        read_device(self.device.as_ref(), self.data_offset + offset, buf)
+    }
+}
+
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    // * は 0 文字以上、? は任意の 1 文字にマッチする
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // 直前の * がもう 1 文字多くマッチするとみなしてやり直す
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    // CRC-32/ISO-HDLC (zlib・PNG などと同じ。多項式 0xEDB88320 (反転), 初期値・最終 XOR 0xFFFFFFFF)
+    let mut crc = !crc;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn read_device(device: &dyn BlockDevice, offset: u64, buf: &mut [u8]) -> Result<(), Box<dyn StdError>> {
+    // セクタ境界にそろっていない範囲も読めるように、セクタ単位で読んで切り出す
+    let sector_size = device.sector_size();
+    let mut sector = vec![0; sector_size];
+    let mut done = 0;
+    while done < buf.len() {
+        let pos = offset + done as u64;
+        let within = (pos % sector_size as u64) as usize;
+        let len = (sector_size - within).min(buf.len() - done);
+        device.read_sector(pos / sector_size as u64, &mut sector)?;
+        buf[done..done + len].copy_from_slice(&sector[within..within + len]);
+        done += len;
+    }
+    Ok(())
+}
+
+fn data_cluster_count(bpb: &Fat16BPB) -> u32 {
+    // データ領域のセクタ数 = 全体 - 予約領域 - FAT 領域 - ルートディレクトリ領域
+    let total_sectors = if bpb.total_sectors == 0 { bpb.large_sectors } else { bpb.total_sectors as u32 };
+    let root_dir_sectors = (bpb.root_entry_count as u32 * 32).div_ceil((bpb.bytes_per_sector as u32).max(1));
+    let metadata_sectors = bpb.reserved_sector_count as u32 + bpb.num_fats as u32 * bpb.sectors_per_fat as u32 + root_dir_sectors;
+    total_sectors.saturating_sub(metadata_sectors) / (bpb.sectors_per_cluster as u32).max(1)
+}
+
+fn check_bytes_per_sector(bytes_per_sector: u16) -> Result<(), Box<dyn StdError>> {
+    // セクタサイズは 512, 1024, 2048, 4096 のいずれか (0 などは後続の計算を壊す)
+    if !matches!(bytes_per_sector, 512 | 1024 | 2048 | 4096) {
+        return Err(format!("Invalid bytes_per_sector: {} (must be a power of two between 512 and 4096)", bytes_per_sector).into());
+    }
+    Ok(())
+}
+
+fn check_start_cluster(entry: &Fat16DirEntry) -> Result<(), Box<dyn StdError>> {
+    // クラスタ 0, 1 は予約 (0 は空のファイル、または .. がルートを指す場合のみ使われる)
+    if entry.first_cluster == 1 {
+        return Err(format!("invalid start cluster {}", entry.first_cluster).into());
+    }
+    // クラスタを持たないのにサイズがあるファイルは、空のファイルとして読まずにエラーにする
+    if entry.first_cluster == 0 && entry.file_size != 0 {
+        return Err(format!("invalid start cluster 0 (file_size = {})", entry.file_size).into());
+    }
+    Ok(())
+}
+
+// Fat16 は Send であること (ブロックデバイスやチェーンのキャッシュを持っていてもスレッド間で受け渡せる)
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Fat16>();
+};
+
+// ディレクトリエントリの格納場所
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirLocation {
+    // ルートディレクトリ (固定サイズの領域)
+    Root,
+    // サブディレクトリ (先頭クラスタ)
+    Cluster(u16),
+}
+
+#[derive(Debug)]
+pub struct Fat16 {
+    pub bpb: Fat16BPB,
+    pub ebpb: Fat16EBPB,
+    pub alloc_table: Fat16AllocTable,
+    pub root_dir: Vec<Fat16DirEntry>,
+    // データ領域 (ブロックデバイスから読む場合は空)
+    pub clusters: Vec<u8>,
+    // ルートディレクトリ領域 (書き込み用)
+    root_dir_bytes: Vec<u8>,
+    // 予約領域 (ブートセクタ・ブートローダなど)
+    reserved_region: Vec<u8>,
+    // 書き込み時に最後のクラスタの余りをゼロで埋めるか
+    zero_fill_slack: bool,
+    // 先頭バイトが 0x00 のエントリでディレクトリの読み込みを打ち切るか
+    stop_at_terminator: bool,
+    reader: Option<ClusterReader>,
+    // イメージ全体のバイト数
+    image_len: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageGeometry {
+    // BPB 上のボリュームサイズ
+    pub total_bytes: u64,
+    // 予約領域 + FAT 領域 + ルートディレクトリ領域
+    pub metadata_bytes: u64,
+    // データ領域
+    pub data_bytes: u64,
+    // データクラスタ数
+    pub cluster_count: u32,
+    // イメージファイルの実サイズ
+    pub image_bytes: u64,
+    // ボリューム末尾以降の余り (イメージがボリュームより大きい場合)
+    pub slack_bytes: u64,
+}
+
+// fsstat のような診断表示向けのボリューム情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeSummary {
+    pub bytes_per_sector: u16,
+    pub bytes_per_cluster: usize,
+    // データクラスタ数
+    pub total_clusters: u32,
+    pub free_clusters: u32,
+    pub num_fats: u8,
+    pub root_entry_count: u16,
+    pub volume_label: String,
+    // "XXXX-XXXX" 形式
+    pub volume_id: String,
+    pub fat_type: FatType,
+}
+
+// ディレクトリ一覧の並び順
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    // 名前 (大文字小文字を区別しない)
+    Name,
+    // ファイルサイズ
+    Size,
+    // 最終更新日時
+    ModifiedTime,
+    // ディレクトリ上の順序のまま
+    None,
+}
+
+impl Fat16 {
+    #[cfg(feature = "std")]
+    pub fn new<P: AsRef<StdPath>>(path: P) -> Result<Fat16, Box<dyn StdError>> {
+        // ファイルを読み込む
+        let mut file = File::open(path).unwrap();
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).unwrap();
+
+        Fat16::from_bytes(bytes)
+    }
+
+    #[cfg(feature = "sha2")]
+    pub fn open_verified<P: AsRef<StdPath>>(path: P, expected_sha256: [u8; 32]) -> Result<Fat16, Box<dyn StdError>> {
+        // ファイルを読み込む
+        let bytes = std::fs::read(path)?;
+
+        // パースする前にイメージ全体のハッシュを検証する
+        let digest: [u8; 32] = Sha256::digest(&bytes).into();
+        if digest != expected_sha256 {
+            return Err("Image SHA-256 digest does not match".into());
+        }
+
+        Fat16::from_bytes(bytes)
+    }
+
+    #[cfg(feature = "flate2")]
+    pub fn from_gz<P: AsRef<StdPath>>(path: P) -> Result<Fat16, Box<dyn StdError>> {
+        // .img.gz を展開してからメモリ上でパースする
+        let mut bytes = Vec::new();
+        GzDecoder::new(File::open(path)?).read_to_end(&mut bytes)?;
+        Fat16::from_bytes(bytes)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_partition<P: AsRef<StdPath>>(path: P, index: usize) -> Result<Fat16, Box<dyn StdError>> {
+        // ファイルを読み込む
+        let mut file = File::open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        // MBR のパーティションテーブルから対象パーティションを選ぶ
+        let (mbr, _) = Mbr::parse(&bytes)?;
+        let partition = mbr.partitions
+            .get(index)
+            .ok_or(format!("Partition index out of range. index = {}", index))?;
+        if !partition.is_fat16() {
+            return Err(format!("Partition {} is not FAT16. type = {:#04x}", index, partition.partition_type).into());
+        }
+
+        // パーティション先頭からパース
+        let head = partition.start_lba as usize * 512;
+        if head >= bytes.len() {
+            return Err(format!("Partition start out of range. start_lba = {}", partition.start_lba).into());
+        }
+        bytes.drain(..head);
+        Fat16::from_bytes(bytes)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn open<P: AsRef<StdPath>>(path: P) -> Result<Fat16, Box<dyn StdError>> {
+        // ファイルをブロックデバイスとして開き、データ領域は必要になった時に読む
+        Fat16::from_device(IoDevice::new(File::open(path)?))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read + Seek + Send + 'static>(reader: R) -> Result<Fat16, Box<dyn StdError>> {
+        Fat16::from_device(IoDevice::new(reader))
+    }
+
+    pub fn from_device<D: BlockDevice + 'static>(device: D) -> Result<Fat16, Box<dyn StdError>> {
+        // BPB / EBPB を読み込んでメタデータ領域のサイズを求める
+        let mut boot_sector = vec![0; 512];
+        read_device(&device, 0, &mut boot_sector)?;
+        let (bpb, _) = Fat16BPB::parse(&boot_sector)?;
+
+        // 予約領域 + FAT 領域 + ルートディレクトリ領域
+        let reserved_size = bpb.reserved_sector_count as usize * bpb.bytes_per_sector as usize;
+        let fat_size = bpb.num_fats as usize * bpb.sectors_per_fat as usize * bpb.bytes_per_sector as usize;
+        let root_dir_size = (bpb.root_entry_count as usize * 32).div_ceil(bpb.bytes_per_sector as usize) * bpb.bytes_per_sector as usize;
+        let data_offset = reserved_size + fat_size + root_dir_size;
+
+        // メタデータ領域のみ読み込んでパースし、データ領域は必要になった時に読む
+        let image_len = device.sector_count() * device.sector_size() as u64;
+        let mut bytes = vec![0; data_offset];
+        read_device(&device, 0, &mut bytes)?;
+        let mut fs = Fat16::from_bytes(bytes)?;
+        fs.image_len = image_len;
+        fs.reader = Some(ClusterReader {
+            device: Box::new(device),
+            data_offset: data_offset as u64,
+        });
+
+        Ok(fs)
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Fat16, Box<dyn StdError>> {
+        // FAT の種類は file_system_type ではなくクラスタ数で判定する
+        // FAT32 は 16 ビットの sectors_per_fat が 0 なので、FAT をパースする前に判定する
+        let (bpb, _) = Fat16BPB::parse(&bytes)?;
+        let fat_type = bpb.fat_type();
+        if fat_type != FatType::Fat16 {
+            // sectors_per_fat が 0 の場合はクラスタ数を見ずに FAT32 と判定している
+            let reason = if bpb.sectors_per_fat == 0 { "sectors_per_fat = 0" } else { "cluster count" };
+            return Err(format!("Not a FAT16 volume: detected {:?} from {}", fat_type, reason).into());
+        }
+
+        Fat16::parse_image(bytes)
+    }
+
+    // FAT の種類を検証せずにパースする (FAT12 も 16 ビットに展開して読める)
+    fn parse_image(bytes: Vec<u8>) -> Result<Fat16, Box<dyn StdError>> {
+        let image_len = bytes.len() as u64;
+
+        // FAT16 パース
+        let image = &bytes;
+        let (bpb, bytes) = Fat16BPB::parse(image)?;
+        let (ebpb, _) = Fat16EBPB::parse(bytes)?;
+
+        // 予約領域 (ブートセクタを含む reserved_sector_count セクタ) を読み飛ばす
+        let reserved_size = bpb.reserved_sector_count as usize * bpb.bytes_per_sector as usize;
+        if bpb.reserved_sector_count == 0 || reserved_size > image.len() {
+            return Err(format!("Invalid reserved sector count: {}", bpb.reserved_sector_count).into());
+        }
+        let (reserved_region, bytes) = image.split_at(reserved_size);
+        let (alloc_table, bytes) = Fat16AllocTable::parse(bytes, &bpb)?;
+
+        // root_dir_sectors = ((fat_boot->root_entry_count * 32) + (fat_boot->bytes_per_sector - 1)) / fat_boot->bytes_per_sector;
+
+        // Root Directory をパース
+        // root_entry_count が壊れていてイメージに収まらない場合は、通常の長さ不足と区別して報告する
+        let root_dir_len = bpb.root_entry_count as usize * 32;
+        if root_dir_len > bytes.len() {
+            return Err(format!(
+                "root_entry_count ({}) exceeds image size: root directory needs {} bytes, but only {} bytes remain after the FAT",
+                bpb.root_entry_count, root_dir_len, bytes.len(),
+            ).into());
+        }
+        let (root_dir, rest) = Fat16DirEntry::parses_until_terminator(bytes, bpb.root_entry_count)?;
+        let root_dir_bytes = bytes[..bytes.len() - rest.len()].to_vec();
+
+        // データ領域はセクタ境界から始まる
+        let root_dir_size = root_dir_bytes.len().div_ceil(bpb.bytes_per_sector as usize) * bpb.bytes_per_sector as usize;
+        let rest = bytes.get(root_dir_size..).unwrap_or_default();
+
+        Ok(Fat16 {
+            bpb,
+            ebpb,
+            alloc_table,
+            root_dir,
+            clusters: rest.to_vec(),
+            root_dir_bytes,
+            reserved_region: reserved_region.to_vec(),
+            zero_fill_slack: true,
+            stop_at_terminator: true,
+            reader: None,
+            image_len,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // 予約領域 + 全ての FAT + ルートディレクトリ領域 + データ領域 を並べてイメージに戻す
+        let bytes_per_sector = self.bpb.bytes_per_sector as usize;
+        let mut image = self.reserved_region.clone();
+        image.extend_from_slice(&self.alloc_table.to_bytes(&self.bpb));
+
+        // ルートディレクトリ領域はセクタ境界まで埋める
+        let root_dir_size = self.root_dir_bytes.len().div_ceil(bytes_per_sector) * bytes_per_sector;
+        image.extend_from_slice(&self.root_dir_bytes);
+        image.resize(image.len() + root_dir_size - self.root_dir_bytes.len(), 0);
+
+        // ブロックデバイスから読んでいる場合はデータ領域をまとめて読み込む
+        match &self.reader {
+            Some(reader) => {
+                let mut data = vec![0; self.image_len.saturating_sub(reader.data_offset) as usize];
+                reader.read_at(0, &mut data)?;
+                image.extend_from_slice(&data);
+            }
+            None => image.extend_from_slice(&self.clusters),
+        }
+
+        Ok(image)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn save<P: AsRef<StdPath>>(&self, path: P) -> Result<(), Box<dyn StdError>> {
+        std::fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn read_file(&self, path: &Path) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // path にマッチする DirEntry を探す
+        let entry = self.find_dir_entry(path)?;
+        self.read_entry_at(&entry, path.original())
+    }
+
+    pub fn read_entry(&self, entry: &Fat16DirEntry) -> Result<Vec<u8>, Box<dyn StdError>> {
+        self.read_entry_at(entry, &entry.name)
+    }
+
+    // path はチェーンが壊れていた場合のエラー表示用
+    fn read_entry_at(&self, entry: &Fat16DirEntry, path: &str) -> Result<Vec<u8>, Box<dyn StdError>> {
+        check_start_cluster(entry)?;
+        self.check_file_size(entry)?;
+
+        // 空のファイルはクラスタを持たない
+        if entry.first_cluster == 0 {
+            return Ok(vec![]);
+        }
+
+        // FAT テーブルの参照
+        // クラスタを辿ってデータを取得
+        let cluster_chain = self.alloc_table
+            .get_cluster_chain(entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: path.to_string(), error })?;
+        let mut file = self.read_clusters(cluster_chain)?;
+        file.truncate(entry.file_size as usize);
+
+        Ok(file)
+    }
+
+    pub fn read_file_contiguous(&self, path: &Path) -> Result<Option<&[u8]>, Box<dyn StdError>> {
+        // path にマッチする DirEntry を探す
+        let entry = self.find_dir_entry(path)?;
+        self.check_file_size(&entry)?;
+        if entry.first_cluster == 0 {
+            return Ok(Some(&[]));
+        }
+
+        // ブロックデバイスから読む場合はデータ領域を持っていないので借用できない
+        if self.reader.is_some() {
+            return Ok(None);
+        }
+
+        // クラスタが連続していて、file_size を全て含む場合のみデータ領域をそのまま返す
+        let cluster_chain = self.alloc_table
+            .get_cluster_chain(entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?;
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let file_size = entry.file_size as usize;
+
+        // チェーンが file_size より短い・データ領域の範囲外 (イメージが壊れている) 場合は None ではなくエラー
+        if cluster_chain.len() * bytes_per_cluster < file_size {
+            return Err(format!("Cluster chain of '{}' is shorter than its file size ({} bytes)", path.original(), file_size).into());
+        }
+        let contiguous = cluster_chain.windows(2).all(|pair| pair[1] == pair[0].wrapping_add(1));
+        if !contiguous {
+            return Ok(None);
+        }
+        let head = (entry.first_cluster as usize - 2) * bytes_per_cluster;
+        let data = self.clusters
+            .get(head..head + file_size)
+            .ok_or(format!("Cluster number out of range. len = {}", self.clusters.len()))?;
+        Ok(Some(data))
+    }
+
+    pub fn read_file_untruncated(&self, path: &Path) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // path にマッチする DirEntry を探す
+        let entry = self.find_dir_entry(path)?;
+
+        // file_size で切り詰めず、最後のクラスタの余り (スラック) も含めて返す
+        if entry.first_cluster == 0 {
+            return Ok(vec![]);
+        }
+        let cluster_chain = self.alloc_table
+            .get_cluster_chain(entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?;
+        self.read_clusters(cluster_chain)
+    }
+
+    pub fn read_file_verify_order(&self, path: &Path) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // path にマッチする DirEntry を探す
+        let entry = self.find_dir_entry(path)?;
+        self.check_file_size(&entry)?;
+
+        // 各クラスタを 1 度だけ辿ることを確認しながらチェーンを取得
+        let cluster_chain = self.alloc_table
+            .get_checked_cluster_chain(entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?;
+        let mut file = self.read_clusters(cluster_chain)?;
+        file.truncate(entry.file_size as usize);
+
+        Ok(file)
+    }
+
+    pub fn verify_file_size(&self, path: &Path) -> Result<(), Box<dyn StdError>> {
+        // path にマッチする DirEntry を探す
+        let entry = self.find_dir_entry(path)?;
+        if entry.is_dir() || entry.is_volume_label() {
+            return Err(format!("Not a file: {}", path.original()).into());
+        }
+
+        // チェーンの長さが ceil(file_size / bytes_per_cluster) と一致するか
+        let chain_clusters = self.alloc_table
+            .get_checked_cluster_chain(entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?
+            .len();
+        let expected_clusters = (entry.file_size as usize).div_ceil(self.bytes_per_cluster());
+        if chain_clusters != expected_clusters {
+            return Err(Fat16Error::FileSizeMismatch {
+                path: path.original().to_string(),
+                file_size: entry.file_size,
+                expected_clusters,
+                chain_clusters,
+            }.into());
+        }
+
+        Ok(())
+    }
+
+    pub fn for_each_chunk(&self, path: &Path, mut f: impl FnMut(&[u8])) -> Result<(), Box<dyn StdError>> {
+        // ファイル全体を確保せず、クラスタ毎に file_size までの内容を渡す
+        let entry = self.find_dir_entry(path)?;
+        self.check_file_size(&entry)?;
+        let cluster_chain = self.alloc_table
+            .get_cluster_chain(entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?;
+        let mut remaining = entry.file_size as usize;
+        for cluster_number in cluster_chain {
+            if remaining == 0 {
+                break;
+            }
+            let cluster_data = self.read_cluster(cluster_number)?;
+            let len = remaining.min(cluster_data.len());
+            f(&cluster_data[..len]);
+            remaining -= len;
+        }
+
+        // チェーンが file_size より短い (FAT が壊れている) 場合は途中までの内容で終わらせない
+        if remaining > 0 {
+            return Err(format!("Cluster chain of '{}' is shorter than its file size ({} bytes)", path.original(), entry.file_size).into());
+        }
+        Ok(())
+    }
+
+    pub fn file_checksum(&self, path: &Path) -> Result<u32, Box<dyn StdError>> {
+        // CRC-32/ISO-HDLC (zlib の crc32 と同じ値)
+        // 別のハッシュを使う場合は for_each_chunk に渡す
+        let mut crc = 0;
+        self.for_each_chunk(path, |chunk| crc = crc32_update(crc, chunk))?;
+        Ok(crc)
+    }
+
+    pub fn read_file_tail(&self, path: &Path, n: usize) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // path にマッチする DirEntry を探す
+        let entry = self.find_dir_entry(path)?;
+        self.check_file_size(&entry)?;
+
+        // 読み始めのオフセットまでクラスタ単位で読み飛ばす
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let file_size = entry.file_size as usize;
+        let offset = file_size.saturating_sub(n);
+        let skip = offset / bytes_per_cluster;
+
+        let cluster_chain = self.alloc_table
+            .get_cluster_chain(entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?;
+        let mut file = self.read_clusters(cluster_chain.into_iter().skip(skip))?;
+
+        // 先頭の端数と file_size 以降を除去
+        file.truncate(file_size - skip * bytes_per_cluster);
+        file.drain(..(offset % bytes_per_cluster).min(file.len()));
+
+        Ok(file)
+    }
+
+    pub fn read_file_range(&self, path: &Path, offset: u64, len: usize) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // path にマッチする DirEntry を探す
+        let entry = self.find_dir_entry(path)?;
+        self.check_file_size(&entry)?;
+
+        // 範囲を file_size に収める
+        let file_size = entry.file_size as usize;
+        let start = (offset.min(file_size as u64)) as usize;
+        let end = start.saturating_add(len).min(file_size);
+        if start == end {
+            return Ok(vec![]);
+        }
+
+        // 範囲を含むクラスタのみ読み込む
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let skip = start / bytes_per_cluster;
+        let take = end.div_ceil(bytes_per_cluster) - skip;
+        let cluster_chain = self.alloc_table
+            .get_cluster_chain(entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?;
+        let data = self.read_clusters(cluster_chain.into_iter().skip(skip).take(take))?;
+
+        // チェーンが file_size より短い (FAT が壊れている) 場合は範囲を読み切れない
+        let head = skip * bytes_per_cluster;
+        if data.len() < end - head {
+            return Err(format!("Cluster chain of '{}' is shorter than its file size ({} bytes)", path.original(), file_size).into());
+        }
+        Ok(data[start - head..end - head].to_vec())
+    }
+
+    pub fn resolve_chain(&self, path: &Path) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        // path の各要素に対応する DirEntry を順に集める
+        let dirs = path.parse();
+
+        // ルートディレクトリは複製せずに借用し、サブディレクトリのみ読み込む
+        let mut entry: Cow<'_, [Fat16DirEntry]> = Cow::Borrowed(&self.root_dir);
+        let mut dir_path = String::new();
+        let mut chain = vec![];
+        for (idx, dir) in dirs.iter().enumerate() {
+            let d = entry
+                .iter()
+                .find(|e| e.matches(dir))
+                .ok_or("No such file or direcotry")?
+                .clone();
+            if idx < dirs.len() - 1 {
+                dir_path = format!("{}/{}", dir_path, dir);
+                entry = Cow::Owned(self.read_dir_entry(&d, &dir_path)?);
+            }
+            chain.push(d);
+        }
+
+        Ok(chain)
     }
+
+    pub fn find_exact(&self, path: &Path) -> Result<Fat16DirEntry, Box<dyn StdError>> {
+        // 入力されたままの名前で辿り、大文字・小文字を区別して比較する
+        let dirs = path.original()[1..].split('/').filter(|dir| !dir.is_empty()).collect::<Vec<_>>();
+
+        let mut entry: Cow<'_, [Fat16DirEntry]> = Cow::Borrowed(&self.root_dir);
+        let mut dir_path = String::new();
+        let mut found = None;
+        for (idx, dir) in dirs.iter().enumerate() {
+            let d = entry
+                .iter()
+                .find(|e| e.name == *dir || e.short_name == *dir)
+                .ok_or("No such file or direcotry")?
+                .clone();
+            if idx < dirs.len() - 1 {
+                dir_path = format!("{}/{}", dir_path, dir);
+                entry = Cow::Owned(self.read_dir_entry(&d, &dir_path)?);
+            }
+            found = Some(d);
+        }
+
+        Ok(found.ok_or("Root directory has no directory entry")?)
+    }
+
+    pub fn open_dir(&self, path: &Path) -> Result<Directory<'_>, Box<dyn StdError>> {
+        // パスを解決してディレクトリの場所を保持する
+        let location = self.dir_location(path)?;
+        Ok(Directory { fs: self, path: path.clone(), location })
+    }
+
+    pub fn exists(&self, path: &Path) -> bool {
+        path.is_root() || self.find_dir_entry(path).is_ok()
+    }
+
+    pub fn is_dir(&self, path: &Path) -> Result<bool, Box<dyn StdError>> {
+        if path.is_root() {
+            return Ok(true);
+        }
+        Ok(self.find_dir_entry(path)?.is_dir())
+    }
+
+    pub fn is_file(&self, path: &Path) -> Result<bool, Box<dyn StdError>> {
+        if path.is_root() {
+            return Ok(false);
+        }
+        let entry = self.find_dir_entry(path)?;
+        Ok(!entry.is_dir() && !entry.is_volume_label())
+    }
+
+    pub fn read_directory(&self, path: &Path) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        // ルートディレクトリは DirEntry を持たないのでそのまま返す
+        if path.is_root() {
+            return Ok(self.root_dir.clone());
+        }
+
+        // path にマッチする DirEntry を探す
+        let entry = self.find_dir_entry(path)?;
+        self.read_dir_entry(&entry, &path.abs_path)
+    }
+
+    pub fn read_directory_filtered(&self, path: &Path, show_hidden: bool) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        // ls と同様に、隠し・システム属性のエントリは show_hidden の場合のみ含める
+        let entries = self.read_directory(path)?
+            .into_iter()
+            .filter(|entry| show_hidden || (!entry.is_hidden() && !entry.is_system()))
+            .collect();
+        Ok(entries)
+    }
+
+    pub fn read_directory_sorted(&self, path: &Path, sort: SortKey) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        // 同じキーのエントリはディレクトリ上の順序を保つ (安定ソート)
+        let mut entries = self.read_directory(path)?;
+        match sort {
+            SortKey::Name => entries.sort_by_cached_key(|entry| entry.name.to_lowercase()),
+            SortKey::Size => entries.sort_by_key(|entry| entry.file_size),
+            SortKey::ModifiedTime => entries.sort_by_key(|entry| (entry.last_modify_date.to_u16(), entry.last_modify_time.to_u16())),
+            SortKey::None => {}
+        }
+        Ok(entries)
+    }
+
+    pub fn read_dir_raw(&self, path: &Path) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // 32 バイトのレコードにパースする前の、ディレクトリのクラスタを連結したバイト列
+        let location = self.dir_location(path)?;
+        self.read_dir_stream(location)
+    }
+
+    pub fn walk(&self, root: &Path) -> Result<Vec<(Path, Fat16DirEntry)>, Box<dyn StdError>> {
+        // root 以下の全エントリを深さ優先で列挙する
+        let entries = self.read_directory(root)?;
+
+        let mut found = vec![];
+        self.walk_in(entries, root.original().trim_end_matches('/'), &mut found)?;
+        Ok(found)
+    }
+
+    pub fn tree(&self, root: &Path) -> Result<String, Box<dyn StdError>> {
+        // tree コマンド風に root 以下を描画する (各行はエントリの Display)
+        let prefix = root.original().trim_end_matches('/').len();
+        let nodes = self.walk(root)?
+            .into_iter()
+            .filter(|(_, entry)| !entry.is_volume_label())
+            .map(|(path, entry)| (path.original()[prefix..].matches('/').count(), entry))
+            .collect::<Vec<_>>();
+
+        let mut rendered = format!("{}\n", if root.is_root() { "/" } else { root.original() });
+        let mut last_flags: Vec<bool> = vec![];
+        for (idx, (depth, entry)) in nodes.iter().enumerate() {
+            // 同じ階層の後続エントリがなければ末尾
+            let is_last = nodes[idx + 1..]
+                .iter()
+                .take_while(|(d, _)| d >= depth)
+                .all(|(d, _)| d != depth);
+            last_flags.truncate(depth - 1);
+
+            for &parent_is_last in &last_flags {
+                rendered.push_str(if parent_is_last { "    " } else { "│   " });
+            }
+            rendered.push_str(if is_last { "└── " } else { "├── " });
+            rendered.push_str(&format!("{}\n", entry));
+            last_flags.push(is_last);
+        }
+
+        Ok(rendered)
+    }
+
+    pub fn find_by_attribute(&self, root: &Path, mask: u8, value: u8) -> Result<Vec<(Path, Fat16DirEntry)>, Box<dyn StdError>> {
+        // attribute & mask == value となるエントリのみ抽出
+        let found = self.walk(root)?
+            .into_iter()
+            .filter(|(_, entry)| entry.attribute & mask == value)
+            .collect();
+        Ok(found)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn extract_to(&self, src: &Path, dest: &StdPath) -> Result<(), Box<dyn StdError>> {
+        // src 以下を dest 以下に書き出す (. と .. は walk で除かれる)
+        std::fs::create_dir_all(dest)?;
+        let prefix = src.original().trim_end_matches('/').len();
+        let mut created = alloc::collections::BTreeSet::new();
+        // ディレクトリのパス (src からの相対) と、実際に作成したホスト側のディレクトリ
+        let mut host_dirs: BTreeMap<String, std::path::PathBuf> = BTreeMap::new();
+        for (path, entry) in self.walk(src)? {
+            if entry.is_volume_label() {
+                continue;
+            }
+
+            // 親ディレクトリが名前を変えて作成されていても、その下に書き出す
+            let relative = &path.original()[prefix..];
+            let (parent, name) = relative.rsplit_once('/').unwrap_or(("", relative));
+            let host_parent = host_dirs.get(parent).map_or(dest, |dir| dir.as_path());
+
+            // 大文字・小文字を区別しないホストで衝突する場合は "_N" を付ける
+            let mut host_path = host_parent.join(name);
+            let mut n = 1;
+            while !created.insert(host_path.to_string_lossy().to_lowercase()) {
+                let name = match name.rfind('.') {
+                    Some(idx) if idx > 0 => format!("{}_{}{}", &name[..idx], n, &name[idx..]),
+                    _ => format!("{}_{}", name, n),
+                };
+                host_path = host_path.with_file_name(name);
+                n += 1;
+            }
+
+            if entry.is_dir() {
+                std::fs::create_dir_all(&host_path)?;
+                host_dirs.insert(relative.to_string(), host_path);
+            } else {
+                let file = File::create(&host_path)?;
+                std::io::Write::write_all(&mut &file, &self.read_entry_at(&entry, path.original())?)?;
+                file.set_modified(entry.last_modify_date.to_system_time(&entry.last_modify_time))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn import_from(&mut self, host_dir: &StdPath, dest: &Path) -> Result<(), Box<dyn StdError>> {
+        // host_dir 直下のエントリを名前順に dest 以下へ作成する
+        let mut host_entries = std::fs::read_dir(host_dir)?.collect::<Result<Vec<_>, _>>()?;
+        host_entries.sort_by_key(|entry| entry.file_name());
+
+        let dest_path = dest.original().trim_end_matches('/');
+        for host_entry in host_entries {
+            let name = host_entry.file_name().into_string().map_err(|name| format!("Invalid file name: {:?}", name))?;
+            let path = Path::from(format!("{}/{}", dest_path, name).as_str());
+            let metadata = host_entry.metadata()?;
+            if metadata.is_dir() {
+                self.create_dir(&path)?;
+                self.import_from(&host_entry.path(), &path)?;
+            } else {
+                self.create_file(&path)?;
+                self.write_file(&path, &std::fs::read(host_entry.path())?)?;
+            }
+
+            // ホストの更新日時を引き継ぐ
+            let (date, time) = Fat16Date::from_system_time(metadata.modified()?);
+            self.set_modified(&path, &date, &time)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn set_modified(&mut self, path: &Path, date: &Fat16Date, time: &Fat16Time) -> Result<(), Box<dyn StdError>> {
+        self.ensure_writable()?;
+
+        // 更新日時と最終アクセス日を書き換える
+        let (location, index, _) = self.find_entry_slot(path)?;
+        let mut record: [u8; 32] = self.read_dir_stream(location)?[index * 32..(index + 1) * 32].try_into()?;
+        set_record_modified(&mut record, date, time);
+        self.write_dir_records(location, index, &[record])
+    }
+
+    pub fn update_entry(&mut self, path: &Path, f: impl FnOnce(&mut Fat16DirEntry)) -> Result<(), Box<dyn StdError>> {
+        self.ensure_writable()?;
+
+        // f で書き換えたエントリを、ディレクトリ内の SFN レコードの位置に書き戻す
+        let (location, index, mut entry) = self.find_entry_slot(path)?;
+        let (name, short_name) = (entry.name.clone(), entry.short_name.clone());
+        f(&mut entry);
+
+        // 名前は LFN エントリにも関わるので rename で変更する
+        if entry.name != name || entry.short_name != short_name {
+            return Err(format!("Cannot change the name of '{}' in update_entry (use rename)", path.original()).into());
+        }
+
+        let mut record: [u8; 32] = self.read_dir_stream(location)?[index * 32..(index + 1) * 32].try_into()?;
+        set_record_fields(&mut record, &entry);
+        self.write_dir_records(location, index, &[record])
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json_tree(&self, root: &Path) -> Result<String, Box<dyn StdError>> {
+        // root 以下を入れ子の JSON (ディレクトリは children を持つ) にする
+        let entries = self.read_directory(root)?;
+        let tree = self.json_tree_in(entries, root.original().trim_end_matches('/'))?;
+        Ok(serde_json::to_string_pretty(&tree)?)
+    }
+
+    #[cfg(feature = "serde")]
+    fn json_tree_in(&self, entries: Vec<Fat16DirEntry>, dir_path: &str) -> Result<serde_json::Value, Box<dyn StdError>> {
+        let mut nodes = vec![];
+        for entry in entries {
+            // . と .. は辿らない
+            if entry.is_dot() {
+                continue;
+            }
+
+            let mut node = serde_json::to_value(&entry)?;
+            if entry.is_dir() {
+                let path = format!("{}/{}", dir_path, entry.name);
+                let sub_entries = self.read_dir_entry(&entry, &path)?;
+                node["children"] = self.json_tree_in(sub_entries, &path)?;
+            }
+            nodes.push(node);
+        }
+
+        Ok(serde_json::Value::Array(nodes))
+    }
+
+    pub fn glob(&self, dir: &Path, pattern: &str) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        // dir 直下で pattern (* と ?) にマッチするエントリ (大文字・小文字は区別しない)
+        let entries = self.read_directory(dir)?;
+        let pattern = pattern.chars().map(|c| c.to_ascii_lowercase()).collect::<Vec<_>>();
+        let found = entries
+            .into_iter()
+            .filter(|entry| !entry.is_dot() && !entry.is_volume_label())
+            .filter(|entry| {
+                let name = entry.name.chars().map(|c| c.to_ascii_lowercase()).collect::<Vec<_>>();
+                glob_match(&pattern, &name)
+            })
+            .collect();
+        Ok(found)
+    }
+
+    pub fn all_files(&self, root: &Path) -> Result<Vec<(Path, Fat16DirEntry)>, Box<dyn StdError>> {
+        // ディレクトリ・ボリュームラベル以外のエントリのみ抽出
+        let found = self.walk(root)?
+            .into_iter()
+            .filter(|(_, entry)| !entry.is_dir() && !entry.is_volume_label())
+            .collect();
+        Ok(found)
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = Result<(String, Vec<u8>), Box<dyn StdError>>> + '_ {
+        // 未処理のエントリをスタックに積み、ファイルを見つけるたびに読み込んで返す
+        let mut pending = self.root_dir
+            .iter()
+            .rev()
+            .map(|entry| (format!("/{}", entry.name), entry.clone()))
+            .collect::<Vec<_>>();
+        core::iter::from_fn(move || {
+            while let Some((path, entry)) = pending.pop() {
+                // . と .. 、ボリュームラベルは対象外
+                if entry.is_dot() || entry.is_volume_label() {
+                    continue;
+                }
+
+                // サブディレクトリは中身を積んで次へ
+                if entry.is_dir() {
+                    match self.read_dir_entry(&entry, &path) {
+                        Ok(sub_entries) => pending.extend(
+                            sub_entries
+                                .into_iter()
+                                .rev()
+                                .map(|sub_entry| (format!("{}/{}", path, sub_entry.name), sub_entry)),
+                        ),
+                        Err(e) => return Some(Err(e)),
+                    }
+                    continue;
+                }
+
+                return Some(self.read_entry_at(&entry, &path).map(|data| (path, data)));
+            }
+            None
+        })
+    }
+
+    fn walk_in(&self, entries: Vec<Fat16DirEntry>, dir_path: &str, found: &mut Vec<(Path, Fat16DirEntry)>) -> Result<(), Box<dyn StdError>> {
+        for entry in entries {
+            // . と .. は辿らない
+            if entry.is_dot() {
+                continue;
+            }
+
+            let path = format!("{}/{}", dir_path, entry.name);
+            if entry.is_dir() {
+                let sub_entries = self.read_dir_entry(&entry, &path)?;
+                found.push((Path::from(path.as_str()), entry));
+                self.walk_in(sub_entries, &path, found)?;
+            } else {
+                found.push((Path::from(path.as_str()), entry));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn same_file(&self, a: &Path, b: &Path) -> Result<bool, Box<dyn StdError>> {
+        // 先頭クラスタ (FAT における inode 相当) で比較する
+        let entry_a = self.find_dir_entry(a)?;
+        let entry_b = self.find_dir_entry(b)?;
+        if entry_a.first_cluster != 0 || entry_b.first_cluster != 0 {
+            return Ok(entry_a.first_cluster == entry_b.first_cluster);
+        }
+
+        // 空ファイルはクラスタを持たないので、正規化したパスで比較する
+        Ok(self.canonicalize(a)? == self.canonicalize(b)?)
+    }
+
+    pub fn canonicalize(&self, path: &Path) -> Result<String, Box<dyn StdError>> {
+        // 各階層の DirEntry を辿り、ディスク上の名前でパスを組み立て直す
+        let dirs = path.parse();
+
+        // ルートディレクトリは複製せずに借用し、サブディレクトリのみ読み込む
+        let mut entry: Cow<'_, [Fat16DirEntry]> = Cow::Borrowed(&self.root_dir);
+        let mut canonical = String::new();
+        for (idx, dir) in dirs.iter().enumerate() {
+            let d = entry
+                .iter()
+                .find(|e| e.matches(dir))
+                .ok_or("No such file or direcotry")?;
+            canonical = format!("{}/{}", canonical, d.name);
+            if idx < dirs.len() - 1 {
+                entry = Cow::Owned(self.read_dir_entry(d, &canonical)?);
+            }
+        }
+
+        if canonical.is_empty() {
+            canonical.push('/');
+        }
+        Ok(canonical)
+    }
+
+    pub fn bytes_per_cluster(&self) -> usize {
+        // (B / S) * (S / C)
+        // B / C
+        self.bpb.bytes_per_sector as usize * self.bpb.sectors_per_cluster as usize
+    }
+
+    pub fn root_dir_sectors(&self) -> u32 {
+        // ルートディレクトリ領域はセクタ単位に切り上げる
+        (self.bpb.root_entry_count as u32 * 32).div_ceil(self.bpb.bytes_per_sector as u32)
+    }
+
+    pub fn data_region_offset(&self) -> usize {
+        // イメージ先頭からデータ領域 (クラスタ 2) までのバイト数
+        self.metadata_sectors() as usize * self.bpb.bytes_per_sector as usize
+    }
+
+    pub fn cluster_count(&self) -> u32 {
+        // データ領域のセクタ数 = 全体 - 予約領域 - FAT 領域 - ルートディレクトリ領域
+        let data_sectors = self.total_sectors().saturating_sub(self.metadata_sectors());
+        data_sectors / self.bpb.sectors_per_cluster as u32
+    }
+
+    pub fn root_capacity(&self) -> u16 {
+        // FAT16 のルートディレクトリは固定サイズで拡張できない
+        self.bpb.root_entry_count
+    }
+
+    pub fn deleted_entries(&self, dir: &Path) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        // dir 直下の削除済みエントリ (名前の先頭は '_')
+        let stream = self.read_dir_stream(self.dir_location(dir)?)?;
+        let mut deleted = vec![];
+        for record in stream.chunks_exact(32).filter(|record| record[0] == 0xE5 && record[11] != 0x0f) {
+            let (entries, _) = Fat16DirEntry::parses_with_deleted(record, 1)?;
+            deleted.extend(entries);
+        }
+        Ok(deleted)
+    }
+
+    pub fn root_used_entries(&self) -> usize {
+        // 削除済みを除いた有効なエントリ数 (ボリュームラベルを含む)
+        // BPB 上のルートディレクトリの容量は root_capacity
+        self.root_dir.len()
+    }
+
+    pub fn file_count(&self, dir: &Path) -> Result<usize, Box<dyn StdError>> {
+        // dir 直下のディレクトリ・ボリュームラベル以外のエントリ数
+        let entries: Cow<'_, [Fat16DirEntry]> = if dir.is_root() {
+            Cow::Borrowed(&self.root_dir)
+        } else {
+            Cow::Owned(self.read_directory(dir)?)
+        };
+        Ok(entries.iter().filter(|entry| !entry.is_dir() && !entry.is_volume_label()).count())
+    }
+
+    pub fn root_free_slots(&self) -> u16 {
+        // 未使用 (0x00) または削除済み (0xE5) のスロット数
+        self.root_dir_bytes
+            .chunks(32)
+            .filter(|record| record[0] == 0x00 || record[0] == 0xE5)
+            .count() as u16
+    }
+
+    pub fn reserved_region(&self) -> &[u8] {
+        &self.reserved_region
+    }
+
+    pub fn volume_label(&self) -> String {
+        // ルートディレクトリのボリュームラベルエントリを優先し、なければ EBPB のラベルを返す
+        let root_label = self.root_dir_bytes
+            .chunks(32)
+            .take_while(|record| record[0] != 0x00)
+            .find(|record| record[0] != 0xE5 && record[11] != 0x0f && record[11] & 0x08 != 0)
+            .map(|record| &record[0..11]);
+        let label = root_label.unwrap_or(&self.ebpb.volume_label);
+        cp437::decode(label).trim_end_matches(' ').to_string()
+    }
+
+    pub fn fs_type(&self) -> String {
+        // "FAT16   " などの EBPB のファイルシステム種別
+        cp437::decode(&self.ebpb.file_system_type).trim_end_matches(' ').to_string()
+    }
+
+    pub fn oem_name(&self) -> String {
+        // "MSDOS5.0" や "mkfs.fat" などフォーマットしたツールの名前
+        cp437::decode(&self.bpb.oem_name).trim_end_matches(' ').to_string()
+    }
+
+    pub fn boot_code(&self) -> &[u8] {
+        &self.ebpb.boot_code
+    }
+
+    pub fn image_geometry(&self) -> ImageGeometry {
+        let bytes_per_sector = self.bpb.bytes_per_sector as u64;
+        let total_bytes = self.total_sectors() as u64 * bytes_per_sector;
+        let metadata_bytes = (self.metadata_sectors() as u64 * bytes_per_sector).min(total_bytes);
+
+        ImageGeometry {
+            total_bytes,
+            metadata_bytes,
+            data_bytes: total_bytes - metadata_bytes,
+            cluster_count: self.cluster_count(),
+            image_bytes: self.image_len,
+            slack_bytes: self.image_len.saturating_sub(total_bytes),
+        }
+    }
+
+    pub fn summary(&self) -> VolumeSummary {
+        // 空きクラスタはデータ領域 (クラスタ 2 以降) の FAT エントリのみ数える
+        let total_clusters = self.cluster_count();
+        let free_clusters = (2..total_clusters.saturating_add(2))
+            .filter(|cluster| self.alloc_table.cluster_status(*cluster as u16) == Some(ClusterStatus::Free))
+            .count() as u32;
+        let volume_id = self.ebpb.volume_id;
+
+        VolumeSummary {
+            bytes_per_sector: self.bpb.bytes_per_sector,
+            bytes_per_cluster: self.bytes_per_cluster(),
+            total_clusters,
+            free_clusters,
+            num_fats: self.bpb.num_fats,
+            root_entry_count: self.bpb.root_entry_count,
+            volume_label: self.volume_label(),
+            volume_id: format!("{:04X}-{:04X}", volume_id >> 16, volume_id & 0xFFFF),
+            fat_type: self.alloc_table.fat_type(),
+        }
+    }
+
+    pub fn locate(&self, path: &Path) -> Result<Vec<(u16, usize)>, Box<dyn StdError>> {
+        // チェーン上の各クラスタとイメージ先頭からのバイトオフセット
+        let entry = self.find_dir_entry(path)?;
+        if entry.first_cluster == 0 {
+            return Ok(vec![]);
+        }
+
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let data_offset = self.data_region_offset();
+        let located = self.alloc_table
+            .get_cluster_chain(entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?
+            .into_iter()
+            .map(|cluster| (cluster, data_offset + (cluster as usize - 2) * bytes_per_cluster))
+            .collect();
+        Ok(located)
+    }
+
+    fn total_sectors(&self) -> u32 {
+        if self.bpb.total_sectors == 0 { // total_sectors が 0 の場合は large_sectors を使う
+            self.bpb.large_sectors
+        } else {
+            self.bpb.total_sectors as u32
+        }
+    }
+
+    fn metadata_sectors(&self) -> u32 {
+        // 予約領域 + FAT 領域 + ルートディレクトリ領域
+        self.bpb.reserved_sector_count as u32
+            + self.bpb.num_fats as u32 * self.bpb.sectors_per_fat as u32
+            + self.root_dir_sectors()
+    }
+
+    pub fn allocation_bitmap(&self) -> Vec<bool> {
+        // 予約エントリ (0, 1) を除いたデータクラスタ毎の使用状況 (true = 使用中)
+        let end = (self.cluster_count() as usize + 2).min(self.alloc_table.table.len());
+        self.alloc_table.table[2..end]
+            .iter()
+            .map(|entry| *entry != 0x0000)
+            .collect()
+    }
+
+    pub fn check_crosslinks(&self) -> Result<Vec<CrossLink>, Box<dyn StdError>> {
+        // 複数のチェーンから参照されているクラスタと、それを参照するパスの一覧
+        let mut owners: BTreeMap<u16, Vec<String>> = BTreeMap::new();
+        for (path, entry) in self.walk(&"/".into())? {
+            if entry.is_volume_label() || entry.first_cluster == 0 {
+                continue;
+            }
+            // 壊れたイメージが対象なので、循環・範囲外のチェーンで止まらなくならないよう検査しながら辿る
+            let chain = self.alloc_table
+                .get_checked_cluster_chain(entry.first_cluster as u16)
+                .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?;
+            for cluster in chain {
+                owners.entry(cluster).or_default().push(path.original().to_string());
+            }
+        }
+
+        let crosslinks = owners
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .collect();
+        Ok(crosslinks)
+    }
+
+    pub fn find_lost_clusters(&self) -> Result<Vec<u16>, Box<dyn StdError>> {
+        // ルートから辿れるクラスタに印を付ける
+        let mut reachable = alloc::collections::BTreeSet::new();
+        for (path, entry) in self.walk(&"/".into())? {
+            if entry.is_volume_label() || entry.first_cluster == 0 {
+                continue;
+            }
+            // 循環したチェーンで止まらなくならないよう検査しながら辿る (ディレクトリの中身は walk が検査済み)
+            let chain = self.alloc_table
+                .get_checked_cluster_chain(entry.first_cluster as u16)
+                .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?;
+            reachable.extend(chain);
+        }
+
+        // 使用中なのにどこからも辿れないクラスタ
+        let lost = self.allocation_bitmap()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, allocated)| *allocated)
+            .map(|(idx, _)| idx as u16 + 2)
+            .filter(|cluster| !reachable.contains(cluster))
+            .collect();
+        Ok(lost)
+    }
+
+    pub fn verify_all_chains(&self) -> Result<(), Box<dyn StdError>> {
+        // ルートディレクトリから再帰的に全エントリのチェーンを検査する
+        self.verify_chains_in(&self.root_dir, "")
+    }
+
+    fn verify_chains_in(&self, entries: &[Fat16DirEntry], dir_path: &str) -> Result<(), Box<dyn StdError>> {
+        for entry in entries {
+            // . と .. 、ボリュームラベル、空ファイルは対象外
+            if entry.is_dot() || entry.is_volume_label() || entry.first_cluster == 0 {
+                continue;
+            }
+
+            // チェーンを検査 (データ領域の範囲外も含む)
+            let path = format!("{}/{}", dir_path, entry.name);
+            let chain = self.alloc_table
+                .get_checked_cluster_chain(entry.first_cluster as u16)
+                .map_err(|error| Fat16Error::BrokenChain { path: path.clone(), error })?;
+            if let Some(cluster) = chain.iter().find(|c| self.read_cluster(**c).is_err()) {
+                return Err(Fat16Error::BrokenChain { path, error: ChainError::OutOfRange(*cluster) }.into());
+            }
+
+            // サブディレクトリを再帰的に検査
+            if entry.is_dir() {
+                let sub_entries = self.read_dir_entry(entry, &path)?;
+                self.verify_chains_in(&sub_entries, &path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn cluster_bytes(&self, cluster_number: u16) -> Result<Cow<'_, [u8]>, Box<dyn StdError>> {
+        // データ領域のクラスタは 2 から始まる
+        if cluster_number < 2 {
+            return Err(format!("Cluster number out of range. cluster = {}", cluster_number).into());
+        }
+
+        // メモリ上のイメージは借用、ブロックデバイスからは読み込んだものを返す
+        self.read_cluster(cluster_number)
+    }
+
+    pub fn cluster_bytes_mut(&mut self, cluster_number: u16) -> Result<&mut [u8], Box<dyn StdError>> {
+        self.ensure_writable()?;
+
+        // 範囲チェック
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let head = (cluster_number as usize).checked_sub(2).map(|idx| idx * bytes_per_cluster);
+        match head {
+            Some(head) if head + bytes_per_cluster <= self.clusters.len() => Ok(&mut self.clusters[head..head + bytes_per_cluster]),
+            _ => Err(format!("Cluster number out of range. cluster = {}", cluster_number).into()),
+        }
+    }
+
+    fn check_file_size(&self, entry: &Fat16DirEntry) -> Result<(), Box<dyn StdError>> {
+        // データ領域より大きい file_size は壊れている (巨大な確保を避けるため読む前に弾く)
+        let data_bytes = self.cluster_count() as u64 * self.bytes_per_cluster() as u64;
+        if entry.file_size as u64 > data_bytes {
+            return Err(format!("File size of '{}' ({} bytes) exceeds the data region ({} bytes)", entry.name, entry.file_size, data_bytes).into());
+        }
+        Ok(())
+    }
+
+    fn read_clusters(&self, cluster_chain: impl IntoIterator<Item = u16>) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // クラスタを順に読んで連結する
+        let mut data = Vec::new();
+        for cluster_number in cluster_chain {
+            let cluster_data = self.read_cluster(cluster_number)?;
+            data.extend_from_slice(&cluster_data);
+        }
+        Ok(data)
+    }
+
+    fn read_cluster(&self, cluster_number: u16) -> Result<Cow<'_, [u8]>, Box<dyn StdError>> {
+        // データ領域のクラスタは 2 から始まる
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let head = (cluster_number as usize)
+            .checked_sub(2)
+            .ok_or(format!("Cluster number out of range. cluster = {}", cluster_number))? * bytes_per_cluster;
+
+        // リーダから読み込む場合
+        if let Some(reader) = &self.reader {
+            let mut cluster_data = vec![0; bytes_per_cluster];
+            reader
+                .read_at(head as u64, &mut cluster_data)
+                .map_err(|e| format!("Cluster number out of range. cluster = {} ({})", cluster_number, e))?;
+            return Ok(Cow::Owned(cluster_data));
+        }
+
+        // 範囲チェック
+        if head + bytes_per_cluster > self.clusters.len() {
+            return Err(format!("Cluster number out of range. len = {}", self.clusters.len()).into());
+        }
+
+        Ok(Cow::Borrowed(&self.clusters[head..head + bytes_per_cluster]))
+    }
+
+    fn find_dir_entry(&self, path: &Path) -> Result<Fat16DirEntry, Box<dyn StdError>> {
+        // path にマッチする DirEntry を探す
+        let mut chain = self.resolve_chain(path)?;
+        let entry = chain.pop().ok_or("Root directory has no directory entry")?;
+        check_start_cluster(&entry)?;
+        Ok(entry)
+    }
+
+    fn read_dir_entry(&self, dir_entry: &Fat16DirEntry, dir_path: &str) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        check_start_cluster(dir_entry)?;
+
+        // FAT テーブルの参照
+        // クラスタを辿ってディレクトリ全体のデータを取得 (壊れたイメージで止まらなくならないよう検査しながら辿る)
+        let cluster_chain = self.alloc_table
+            .get_checked_cluster_chain(dir_entry.first_cluster as u16)
+            .map_err(|error| Fat16Error::BrokenChain { path: dir_path.to_string(), error })?;
+        let mut dir_bytes = Vec::new();
+        for cluster_number in cluster_chain {
+            let cluster_data = self.read_cluster(cluster_number).map_err(|_| {
+                Fat16Error::DirectoryChainBroken { path: dir_path.to_string(), cluster: cluster_number }
+            })?;
+            dir_bytes.extend_from_slice(&cluster_data);
+        }
+
+        // クラスタ境界をまたぐ LFN もあるので、まとめて 1 度にパースする
+        self.parse_dir_records(&dir_bytes)
+    }
+
+    fn parse_dir_records(&self, dir_bytes: &[u8]) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        let bytes_per_entry = 32;
+        let num_entry = (dir_bytes.len() / bytes_per_entry) as u16;
+        let (dirs, _) = if self.stop_at_terminator {
+            Fat16DirEntry::parses_until_terminator(dir_bytes, num_entry)?
+        } else {
+            Fat16DirEntry::parses(dir_bytes, num_entry)?
+        };
+        Ok(dirs)
+    }
+
+    pub fn create_dir(&mut self, path: &Path) -> Result<(), Box<dyn StdError>> {
+        self.ensure_writable()?;
+
+        // 親ディレクトリと 8.3 形式の名前 (エイリアス)
+        let location = self.dir_location(&path.parent())?;
+        let short_name = self.new_short_name(location, path)?;
+
+        // ディレクトリ用のクラスタを確保し、. と .. を書き込む
+        let cluster = self.allocate_cluster()?;
+        let parent_cluster = match location {
+            DirLocation::Root => 0,
+            DirLocation::Cluster(parent_cluster) => parent_cluster,
+        };
+        self.write_cluster(cluster, 0, &sfn_record(b".          ", 0x10, cluster, 0))?;
+        self.write_cluster(cluster, 32, &sfn_record(b"..         ", 0x10, parent_cluster, 0))?;
+
+        // 親ディレクトリにエントリを追加
+        self.append_entry(location, path.file_name(), sfn_record(&short_name, 0x10, cluster, 0))
+    }
+
+    pub fn create_file(&mut self, path: &Path) -> Result<(), Box<dyn StdError>> {
+        self.ensure_writable()?;
+
+        // 空のファイルはクラスタを持たない
+        let location = self.dir_location(&path.parent())?;
+        let short_name = self.new_short_name(location, path)?;
+        self.append_entry(location, path.file_name(), sfn_record(&short_name, 0x20, 0, 0))
+    }
+
+    pub fn write_file(&mut self, path: &Path, data: &[u8]) -> Result<(), Box<dyn StdError>> {
+        self.ensure_writable()?;
+
+        let (location, index, entry) = self.find_entry_slot(path)?;
+        if entry.is_dir() || entry.is_volume_label() {
+            return Err(format!("Not a file: {}", path.original()).into());
+        }
+
+        // 必要なクラスタ数に合わせてチェーンを伸縮する
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let needed = data.len().div_ceil(bytes_per_cluster);
+        let mut cluster_chain = match entry.first_cluster {
+            0 => vec![],
+            first_cluster => self.alloc_table
+                .get_cluster_chain(first_cluster as u16)
+                .map_err(|error| Fat16Error::BrokenChain { path: path.original().to_string(), error })?,
+        };
+        if cluster_chain.len() > needed {
+            let released = cluster_chain.split_off(needed);
+            if let Some(last) = cluster_chain.last() {
+                self.alloc_table.set_entry(*last, 0xFFFF);
+            }
+            self.alloc_table.free_chain(released[0]);
+        }
+        while cluster_chain.len() < needed {
+            let cluster = self.alloc_table.allocate_cluster().ok_or("No free cluster")?;
+            if let Some(last) = cluster_chain.last() {
+                self.alloc_table.set_entry(*last, cluster);
+            }
+            cluster_chain.push(cluster);
+        }
+
+        // データを書き込む (最後のクラスタの余りは設定によりゼロで埋める)
+        for (cluster, chunk) in cluster_chain.iter().zip(data.chunks(bytes_per_cluster)) {
+            self.write_cluster(*cluster, 0, chunk)?;
+            if self.zero_fill_slack && chunk.len() < bytes_per_cluster {
+                self.write_cluster(*cluster, chunk.len(), &vec![0; bytes_per_cluster - chunk.len()])?;
+            }
+        }
+
+        // SFN エントリの先頭クラスタ・サイズを更新する
+        let first_cluster = cluster_chain.first().copied().unwrap_or(0);
+        let mut record: [u8; 32] = self.read_dir_stream(location)?[index * 32..(index + 1) * 32].try_into()?;
+        record[26..28].copy_from_slice(&first_cluster.to_le_bytes());
+        record[28..32].copy_from_slice(&(data.len() as u32).to_le_bytes());
+
+        // 更新日時を現在時刻にする (時計のない no_std 環境では set_modified を使う)
+        if let Some((date, time)) = current_timestamp() {
+            set_record_modified(&mut record, &date, &time);
+        }
+        self.write_dir_records(location, index, &[record])
+    }
+
+    pub fn set_len(&mut self, path: &Path, len: u32) -> Result<(), Box<dyn StdError>> {
+        // 伸ばした部分はゼロで埋める
+        let mut data = self.read_file(path)?;
+        data.resize(len as usize, 0);
+        self.write_file(path, &data)
+    }
+
+    pub fn remove_file(&mut self, path: &Path) -> Result<(), Box<dyn StdError>> {
+        self.remove_entry(path, false)
+    }
+
+    pub fn force_remove_file(&mut self, path: &Path) -> Result<(), Box<dyn StdError>> {
+        // 読み取り専用属性のファイルも削除する
+        self.remove_entry(path, true)
+    }
+
+    fn remove_entry(&mut self, path: &Path, force: bool) -> Result<(), Box<dyn StdError>> {
+        self.ensure_writable()?;
+
+        let (location, index, entry) = self.find_entry_slot(path)?;
+        if entry.is_dir() {
+            return Err(format!("Is a directory (use remove_dir): {}", path.original()).into());
+        }
+        if entry.is_volume_label() {
+            return Err(format!("Not a file: {}", path.original()).into());
+        }
+        if entry.attribute & 0x01 != 0 && !force {
+            return Err(format!("Read-only file: {}", path.original()).into());
+        }
+
+        self.mark_deleted(location, index)?;
+
+        // クラスタチェーンを解放する
+        if entry.first_cluster != 0 {
+            self.alloc_table.free_chain(entry.first_cluster as u16);
+        }
+
+        Ok(())
+    }
+
+    pub fn rename(&mut self, from: &Path, to: &Path) -> Result<(), Box<dyn StdError>> {
+        self.ensure_writable()?;
+
+        let (location, index, entry) = self.find_entry_slot(from)?;
+        let to_location = self.dir_location(&to.parent())?;
+        lfn::validate_name(to.file_name())?;
+        if self.exists(to) && !self.same_file(from, to)? {
+            return Err(format!("File exists: {}", to.original()).into());
+        }
+
+        // ディレクトリを自身の配下へは移動できない
+        if entry.is_dir() && !to.parent().is_root() {
+            let parents = self.resolve_chain(&to.parent())?;
+            if parents.iter().any(|e| e.first_cluster == entry.first_cluster) {
+                return Err(format!("Cannot move a directory into itself: {}", to.original()).into());
+            }
+        }
+
+        // 新しい名前のエントリを先に書き込む (先頭クラスタ・日時・サイズは引き継ぐ)
+        // 移動先に空きがなくても元のエントリが消えないよう、削除は書き込めた後に行う
+        let mut sfn: [u8; 32] = self.read_dir_stream(location)?[index * 32..(index + 1) * 32].try_into()?;
+        let mut short_names = lfn::short_names_in(&self.read_dir_stream(to_location)?);
+        if to_location == location {
+            // 同じディレクトリ内では元のエントリの 8.3 形式の名前を使い回せる
+            short_names.retain(|short_name| short_name[..] != sfn[0..11]);
+        }
+        let short_name = lfn::generate_short_name(to.file_name(), &short_names);
+        sfn[0..11].copy_from_slice(&short_name);
+        self.append_entry(to_location, to.file_name(), sfn)?;
+        self.mark_deleted(location, index)?;
+
+        // 別のディレクトリへ移動したディレクトリは .. を付け替える
+        if entry.is_dir() {
+            let parent_cluster = match to_location {
+                DirLocation::Root => 0,
+                DirLocation::Cluster(parent_cluster) => parent_cluster,
+            };
+            // .. の前に LFN を書くツールもあるので、SFN レコードを探す
+            let cluster = entry.first_cluster as u16;
+            let cluster_data = self.read_cluster(cluster)?.into_owned();
+            let offset = cluster_data
+                .chunks(32)
+                .position(|record| &record[0..11] == b"..         " && record[11] != 0x0f)
+                .ok_or("Directory has no '..' entry")? * 32;
+            let mut dotdot: [u8; 32] = cluster_data[offset..offset + 32].try_into()?;
+            dotdot[26..28].copy_from_slice(&parent_cluster.to_le_bytes());
+            self.write_cluster(cluster, offset, &dotdot)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn copy_file(&mut self, from: &Path, to: &Path) -> Result<(), Box<dyn StdError>> {
+        self.ensure_writable()?;
+
+        let (location, index, entry) = self.find_entry_slot(from)?;
+        if entry.is_dir() || entry.is_volume_label() {
+            return Err(format!("Not a file: {}", from.original()).into());
+        }
+        let original: [u8; 32] = self.read_dir_stream(location)?[index * 32..(index + 1) * 32].try_into()?;
+        let data = self.read_entry_at(&entry, from.original())?;
+
+        // コピー先に属性・日時を引き継いだ空のエントリを作る (同名があればエラー)
+        let to_location = self.dir_location(&to.parent())?;
+        let short_name = self.new_short_name(to_location, to)?;
+        let mut sfn = original;
+        sfn[0..11].copy_from_slice(&short_name);
+        sfn[20..22].fill(0);
+        sfn[26..32].fill(0);
+        self.append_entry(to_location, to.file_name(), sfn)?;
+
+        // 新しいクラスタチェーンに書き込み、write_file が更新した日時を元に戻す
+        self.write_file(to, &data)?;
+        let (to_location, to_index, _) = self.find_entry_slot(to)?;
+        let mut record: [u8; 32] = self.read_dir_stream(to_location)?[to_index * 32..(to_index + 1) * 32].try_into()?;
+        record[13..20].copy_from_slice(&original[13..20]);
+        record[22..26].copy_from_slice(&original[22..26]);
+        self.write_dir_records(to_location, to_index, &[record])
+    }
+
+    pub fn set_zero_fill_slack(&mut self, enabled: bool) {
+        // false にすると最後のクラスタの余りを書き換えない (高速だが以前のデータが残る)
+        self.zero_fill_slack = enabled;
+    }
+
+    pub fn set_stop_at_terminator(&mut self, enabled: bool) -> Result<(), Box<dyn StdError>> {
+        // 0x00 の終端を維持していないイメージでは false にして全エントリを読む
+        self.stop_at_terminator = enabled;
+        self.root_dir = self.parse_dir_records(&self.root_dir_bytes)?;
+        Ok(())
+    }
+
+    fn new_short_name(&self, location: DirLocation, path: &Path) -> Result<[u8; 11], Box<dyn StdError>> {
+        let name = path.file_name();
+        lfn::validate_name(name)?;
+
+        // 同名チェック・8.3 形式の名前 (エイリアス) の生成
+        let stream = self.read_dir_stream(location)?;
+        let entries = self.parse_dir_records(&stream)?;
+        if entries.iter().any(|e| e.matches(&name.to_ascii_lowercase())) {
+            return Err(format!("File exists: {}", path.original()).into());
+        }
+        Ok(lfn::generate_short_name(name, &lfn::short_names_in(&stream)))
+    }
+
+    fn append_entry(&mut self, location: DirLocation, name: &str, sfn: [u8; 32]) -> Result<(), Box<dyn StdError>> {
+        // 必要なら LFN エントリを SFN エントリの前に置く
+        let short_name: [u8; 11] = sfn[0..11].try_into()?;
+        let mut records = if lfn::needs_lfn(name, &short_name) {
+            lfn::encode(name, &short_name)
+        } else {
+            vec![]
+        };
+        records.push(sfn);
+        self.append_dir_records(location, &records)
+    }
+
+    fn mark_deleted(&mut self, location: DirLocation, index: usize) -> Result<(), Box<dyn StdError>> {
+        // SFN と、その直前にある同じチェックサムの LFN に削除マーク (0xE5) を付ける
+        let stream = self.read_dir_stream(location)?;
+        let short_name: [u8; 11] = stream[index * 32..index * 32 + 11].try_into()?;
+        let checksum = lfn::checksum(&short_name);
+        let mut head = index;
+        while head > 0 {
+            let record = &stream[(head - 1) * 32..head * 32];
+            if record[11] != 0x0f || record[13] != checksum || record[0] == 0xE5 {
+                break;
+            }
+            head -= 1;
+        }
+        let records = stream[head * 32..(index + 1) * 32]
+            .chunks(32)
+            .map(|record| {
+                let mut record: [u8; 32] = record.try_into().unwrap();
+                record[0] = 0xE5;
+                record
+            })
+            .collect::<Vec<_>>();
+        self.write_dir_records(location, head, &records)
+    }
+
+    fn find_entry_slot(&self, path: &Path) -> Result<(DirLocation, usize, Fat16DirEntry), Box<dyn StdError>> {
+        // 親ディレクトリ内で path の SFN レコードが何番目にあるかを探す
+        let location = self.dir_location(&path.parent())?;
+        let stream = self.read_dir_stream(location)?;
+        let lower_name = path.file_name().to_ascii_lowercase();
+
+        let mut rest = &stream[..];
+        while !rest.is_empty() {
+            // 終端 (0x00) 以降は read_directory と同様に見ない
+            if self.stop_at_terminator && rest[0] == 0x00 {
+                break;
+            }
+            match Fat16DirEntry::parse_entry(rest)? {
+                (Some(entry), next) => {
+                    if entry.matches(&lower_name) {
+                        let index = (stream.len() - next.len()) / 32 - 1;
+                        return Ok((location, index, entry));
+                    }
+                    rest = next;
+                }
+                (None, _) => rest = &rest[32..],
+            }
+        }
+
+        Err("No such file or direcotry".into())
+    }
+
+    fn ensure_writable(&self) -> Result<(), Box<dyn StdError>> {
+        if self.reader.is_some() {
+            return Err("Image opened from a block device is read-only".into());
+        }
+        Ok(())
+    }
+
+    fn dir_location(&self, path: &Path) -> Result<DirLocation, Box<dyn StdError>> {
+        if path.is_root() {
+            return Ok(DirLocation::Root);
+        }
+
+        let entry = self.find_dir_entry(path)?;
+        if !entry.is_dir() {
+            return Err(format!("Not a directory: {}", path.original()).into());
+        }
+        Ok(DirLocation::Cluster(entry.first_cluster as u16))
+    }
+
+    fn read_dir_stream(&self, location: DirLocation) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // ディレクトリの生のバイト列 (32 バイトのレコードの並び)
+        match location {
+            DirLocation::Root => Ok(self.root_dir_bytes.clone()),
+            DirLocation::Cluster(cluster) => self.read_clusters(self.alloc_table.get_cluster_chain(cluster)?),
+        }
+    }
+
+    fn write_dir_records(&mut self, location: DirLocation, index: usize, records: &[[u8; 32]]) -> Result<(), Box<dyn StdError>> {
+        let bytes_per_cluster = self.bytes_per_cluster();
+
+        match location {
+            DirLocation::Root => {
+                // 範囲チェック
+                let (head, tail) = (index * 32, (index + records.len()) * 32);
+                if tail > self.root_dir_bytes.len() {
+                    return Err("Root directory is full".into());
+                }
+                self.root_dir_bytes[head..tail].copy_from_slice(&records.concat());
+
+                // キャッシュしているルートディレクトリを更新
+                self.root_dir = self.parse_dir_records(&self.root_dir_bytes)?;
+            }
+            DirLocation::Cluster(first_cluster) => {
+                let cluster_chain = self.alloc_table.get_cluster_chain(first_cluster)?;
+                for (i, record) in records.iter().enumerate() {
+                    let offset = (index + i) * 32;
+                    let cluster = *cluster_chain
+                        .get(offset / bytes_per_cluster)
+                        .ok_or("Directory entry offset out of range")?;
+                    self.write_cluster(cluster, offset % bytes_per_cluster, record)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append_dir_records(&mut self, location: DirLocation, records: &[[u8; 32]]) -> Result<(), Box<dyn StdError>> {
+        // 連続した空きスロットを探す
+        let stream = self.read_dir_stream(location)?;
+        let slots = stream.len() / 32;
+        let terminator = stream
+            .chunks(32)
+            .position(|record| record[0] == 0x00)
+            .filter(|_| self.stop_at_terminator);
+        if let Some(index) = find_free_slots(&stream, records.len(), self.stop_at_terminator) {
+            self.write_dir_records(location, index, records)?;
+
+            // 終端を使った場合は、直後のスロットを新しい終端にする (終端以降の古いデータを見せない)
+            let tail = index + records.len();
+            if terminator.is_some_and(|terminator| tail > terminator) && tail < slots {
+                self.write_dir_records(location, tail, &[[0u8; 32]])?;
+            }
+            return Ok(());
+        }
+
+        // ルートディレクトリは拡張できない
+        let DirLocation::Cluster(first_cluster) = location else {
+            return Err("Root directory is full".into());
+        };
+
+        // 末尾の空きスロット (終端があれば終端以降) に続くようにクラスタを追加して拡張する
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let trailing_free = stream[..terminator.unwrap_or(slots) * 32]
+            .chunks(32)
+            .rev()
+            .take_while(|record| record[0] == 0x00 || record[0] == 0xE5)
+            .count() + (slots - terminator.unwrap_or(slots));
+        let needed_bytes = (records.len() - trailing_free) * 32;
+        let mut last_cluster = *self.alloc_table.get_cluster_chain(first_cluster)?.last().unwrap();
+        for _ in 0..needed_bytes.div_ceil(bytes_per_cluster) {
+            let cluster = self.allocate_cluster()?;
+            self.alloc_table.set_entry(last_cluster, cluster);
+            last_cluster = cluster;
+        }
+
+        self.write_dir_records(location, slots - trailing_free, records)
+    }
+
+    fn allocate_cluster(&mut self) -> Result<u16, Box<dyn StdError>> {
+        // 空きクラスタを確保してゼロクリアする
+        let cluster = self.alloc_table.allocate_cluster().ok_or("No free cluster")?;
+        let bytes_per_cluster = self.bytes_per_cluster();
+        self.write_cluster(cluster, 0, &vec![0; bytes_per_cluster])?;
+        Ok(cluster)
+    }
+
+    fn write_cluster(&mut self, cluster_number: u16, offset: usize, data: &[u8]) -> Result<(), Box<dyn StdError>> {
+        // データ領域のクラスタは 2 から始まる
+        let bytes_per_cluster = self.bytes_per_cluster();
+        let head = (cluster_number as usize)
+            .checked_sub(2)
+            .ok_or(format!("Cluster number out of range. cluster = {}", cluster_number))? * bytes_per_cluster + offset;
+
+        // 範囲チェック
+        if offset + data.len() > bytes_per_cluster || head + data.len() > self.clusters.len() {
+            return Err(format!("Cluster number out of range. len = {}", self.clusters.len()).into());
+        }
+
+        self.clusters[head..head + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+// for entry in &fs でルートディレクトリのエントリを辿る
+impl<'a> IntoIterator for &'a Fat16 {
+    type Item = &'a Fat16DirEntry;
+    type IntoIter = core::slice::Iter<'a, Fat16DirEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.root_dir.iter()
+    }
+}
+
+// ディレクトリを辿るためのハンドル (パスの解決は開いた時の 1 度のみ)
+#[derive(Debug, Clone)]
+pub struct Directory<'a> {
+    fs: &'a Fat16,
+    path: Path,
+    location: DirLocation,
+}
+
+impl<'a> Directory<'a> {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entries(&self) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        // 保持している場所から直接読む (パスは辿り直さない)
+        match self.location {
+            DirLocation::Root => Ok(self.fs.root_dir.clone()),
+            DirLocation::Cluster(_) => {
+                let stream = self.fs.read_dir_stream(self.location)?;
+                self.fs.parse_dir_records(&stream)
+            }
+        }
+    }
+
+    pub fn cd(&self, name: &str) -> Result<Directory<'a>, Box<dyn StdError>> {
+        match name {
+            "." => Ok(self.clone()),
+            ".." => self.parent(),
+            _ => {
+                let entry = self.entries()?
+                    .into_iter()
+                    .find(|e| e.matches(&name.to_ascii_lowercase()))
+                    .ok_or("No such file or direcotry")?;
+                if !entry.is_dir() {
+                    return Err(format!("Not a directory: {}", name).into());
+                }
+                check_start_cluster(&entry)?;
+
+                let path = format!("{}/{}", self.path.original().trim_end_matches('/'), entry.name);
+                Ok(Directory {
+                    fs: self.fs,
+                    path: Path::from(path.as_str()),
+                    location: DirLocation::Cluster(entry.first_cluster as u16),
+                })
+            }
+        }
+    }
+
+    pub fn parent(&self) -> Result<Directory<'a>, Box<dyn StdError>> {
+        // ルートの親はルート
+        self.fs.open_dir(&self.path.parent())
+    }
+}
+
+// FAT の種類をクラスタ数から判定して開いたボリューム
+#[derive(Debug)]
+pub enum FatVolume {
+    Fat12(Fat16),
+    Fat16(Fat16),
+    Fat32(fat32::Fat32),
+}
+
+impl FatVolume {
+    #[cfg(feature = "std")]
+    pub fn open<P: AsRef<StdPath>>(path: P) -> Result<FatVolume, Box<dyn StdError>> {
+        FatVolume::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<FatVolume, Box<dyn StdError>> {
+        let (bpb, _) = Fat16BPB::parse(&bytes)?;
+        match bpb.fat_type() {
+            FatType::Fat12 => Ok(FatVolume::Fat12(Fat16::parse_image(bytes)?)),
+            FatType::Fat16 => Ok(FatVolume::Fat16(Fat16::from_bytes(bytes)?)),
+            FatType::Fat32 => Ok(FatVolume::Fat32(fat32::Fat32::from_bytes(bytes)?)),
+        }
+    }
+
+    pub fn fat_type(&self) -> FatType {
+        match self {
+            FatVolume::Fat12(_) => FatType::Fat12,
+            FatVolume::Fat16(_) => FatType::Fat16,
+            FatVolume::Fat32(_) => FatType::Fat32,
+        }
+    }
+
+    pub fn root_dir(&self) -> &[Fat16DirEntry] {
+        match self {
+            FatVolume::Fat12(fs) | FatVolume::Fat16(fs) => &fs.root_dir,
+            FatVolume::Fat32(fs) => &fs.root_dir,
+        }
+    }
+
+    pub fn read_file(&self, path: &Path) -> Result<Vec<u8>, Box<dyn StdError>> {
+        match self {
+            FatVolume::Fat12(fs) | FatVolume::Fat16(fs) => fs.read_file(path),
+            FatVolume::Fat32(fs) => fs.read_file(path),
+        }
+    }
+
+    pub fn read_directory(&self, path: &Path) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
+        match self {
+            FatVolume::Fat12(fs) | FatVolume::Fat16(fs) => fs.read_directory(path),
+            FatVolume::Fat32(fs) => fs.read_directory(path),
+        }
+    }
+}
+
+// 空の FAT16 イメージを作成するビルダ
+#[derive(Debug, Clone)]
+pub struct Fat16Builder {
+    bytes_per_sector: u16,
+    total_sectors: u32,
+    // None の場合はクラスタ数が FAT16 の範囲に収まる最小の値を選ぶ
+    sectors_per_cluster: Option<u8>,
+    root_entry_count: u16,
+    num_fats: u8,
+    volume_id: u32,
+    volume_label: [u8; 11],
 }
 
-#[derive(Debug)]
-pub struct Fat16 {
-    pub bpb: Fat16BPB,
-    pub ebpb: Fat16EBPB,
-    pub alloc_table: Fat16AllocTable,
-    pub root_dir: Vec<Fat16DirEntry>,
-    pub clusters: Vec<u8>,
+impl Default for Fat16Builder {
+    fn default() -> Fat16Builder {
+        Fat16Builder::new()
+    }
 }
 
-impl Fat16 {
-    pub fn new<P: AsRef<StdPath>>(path: P) -> Result<Fat16, Box<dyn StdError>> {
-        // ファイルを読み込む
-        let mut file = File::open(path).unwrap();
-        let mut bytes = Vec::new();
-        file.read_to_end(&mut bytes).unwrap();
+impl Fat16Builder {
+    pub fn new() -> Fat16Builder {
+        // 512 バイト/セクタ, 32MB, ルート 512 エントリ, FAT x 2
+        Fat16Builder {
+            bytes_per_sector: 512,
+            total_sectors: 65536,
+            sectors_per_cluster: None,
+            root_entry_count: 512,
+            num_fats: 2,
+            volume_id: 0,
+            volume_label: *b"NO NAME    ",
+        }
+    }
 
-        // FAT16 パース
-        let (bpb, bytes) = Fat16BPB::parse(&bytes)?;
-        let (ebpb, bytes) = Fat16EBPB::parse(&bytes)?;
-        let (alloc_table, bytes) = Fat16AllocTable::parse(&bytes, &bpb)?;
+    pub fn bytes_per_sector(mut self, bytes_per_sector: u16) -> Fat16Builder {
+        self.bytes_per_sector = bytes_per_sector;
+        self
+    }
 
-        // root_dir_sectors = ((fat_boot->root_entry_count * 32) + (fat_boot->bytes_per_sector - 1)) / fat_boot->bytes_per_sector;
+    pub fn total_sectors(mut self, total_sectors: u32) -> Fat16Builder {
+        self.total_sectors = total_sectors;
+        self
+    }
 
-        // Root Directory をパース
-        let (root_dir, bytes) = Fat16DirEntry::parses(&bytes, bpb.root_entry_count)?;
+    pub fn sectors_per_cluster(mut self, sectors_per_cluster: u8) -> Fat16Builder {
+        self.sectors_per_cluster = Some(sectors_per_cluster);
+        self
+    }
 
-        Ok(Fat16 { bpb, ebpb, alloc_table, root_dir, clusters: bytes.to_vec() })
+    pub fn root_entry_count(mut self, root_entry_count: u16) -> Fat16Builder {
+        self.root_entry_count = root_entry_count;
+        self
     }
 
-    pub fn read_file(&self, path: &Path) -> Result<Vec<u8>, Box<dyn StdError>> {
-        // path にマッチする DirEntry を探す
-        let entry = self.find_dir_entry(path)?;
+    pub fn num_fats(mut self, num_fats: u8) -> Fat16Builder {
+        self.num_fats = num_fats;
+        self
+    }
 
-        // FAT テーブルの参照
-        // クラスタを辿ってデータを取得
-        let cluster_chain = self.alloc_table.get_cluster_chain(entry.first_cluster as u16);
-        let mut file = Vec::new();
-        for cluster_number in cluster_chain {
-            let cluster_data = self.read_cluster(cluster_number)?;
-            file.extend(cluster_data);
-        }
-        file.truncate(entry.file_size as usize);
+    pub fn volume_id(mut self, volume_id: u32) -> Fat16Builder {
+        self.volume_id = volume_id;
+        self
+    }
 
-        Ok(file)
+    pub fn volume_label(mut self, volume_label: [u8; 11]) -> Fat16Builder {
+        self.volume_label = volume_label;
+        self
     }
 
-    pub fn read_directory(&self, path: &Path) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
-        // path にマッチする DirEntry を探す
-        let entry = self.find_dir_entry(path)?;
-        self.read_dir_entry(&entry)
+    pub fn build(&self) -> Result<Fat16, Box<dyn StdError>> {
+        Fat16::from_bytes(self.build_bytes()?)
     }
 
-    fn read_cluster<'a>(&'a self, cluster_number: u16) -> Result<&'a [u8], Box<dyn StdError>> {
-        // (B / S) * (S / C)
-        // B / C
-        let bytes_per_cluster = self.bpb.bytes_per_sector as usize * self.bpb.sectors_per_cluster as usize;
-        let head = (cluster_number as usize - 2) * bytes_per_cluster;
+    pub fn build_bytes(&self) -> Result<Vec<u8>, Box<dyn StdError>> {
+        // パラメータの組み合わせを検証する
+        check_bytes_per_sector(self.bytes_per_sector)?;
+        let bytes_per_sector = self.bytes_per_sector as u32;
+        if !(1..=2).contains(&self.num_fats) {
+            return Err(format!("Invalid num_fats: {} (must be 1 or 2)", self.num_fats).into());
+        }
+        if self.root_entry_count == 0 {
+            return Err("Invalid root_entry_count: 0".into());
+        }
+        let (sectors_per_cluster, sectors_per_fat, cluster_count) = match self.sectors_per_cluster {
+            Some(sectors_per_cluster) => {
+                if !sectors_per_cluster.is_power_of_two() {
+                    return Err(format!("Invalid sectors_per_cluster: {} (must be a power of two between 1 and 128)", sectors_per_cluster).into());
+                }
+                let (sectors_per_fat, cluster_count) = self.fat_layout(sectors_per_cluster);
+                (sectors_per_cluster, sectors_per_fat, cluster_count)
+            }
+            None => (0..8)
+                .map(|shift| 1u8 << shift)
+                .map(|sectors_per_cluster| {
+                    let (sectors_per_fat, cluster_count) = self.fat_layout(sectors_per_cluster);
+                    (sectors_per_cluster, sectors_per_fat, cluster_count)
+                })
+                .find(|(_, _, cluster_count)| *cluster_count < 65525)
+                .ok_or(format!("Volume too large for FAT16. total_sectors = {}", self.total_sectors))?,
+        };
+        if FatType::from_cluster_count(cluster_count) != FatType::Fat16 {
+            return Err(format!("Cluster count {} is out of the FAT16 range (4085 - 65524)", cluster_count).into());
+        }
 
-        // 範囲チェック
-        if head + bytes_per_cluster > self.clusters.len() {
-            return Err(format!("Cluster number out of range. len = {}", self.clusters.len()).into());
+        // ブートセクタ (BPB + EBPB)
+        let mut image = vec![0u8; self.total_sectors as usize * bytes_per_sector as usize];
+        let (total_sectors, large_sectors) = match u16::try_from(self.total_sectors) {
+            Ok(total_sectors) => (total_sectors, 0),
+            Err(_) => (0, self.total_sectors),
+        };
+        image[0..3].copy_from_slice(&[0xEB, 0x3C, 0x90]);
+        image[3..11].copy_from_slice(b"MSWIN4.1");
+        image[11..13].copy_from_slice(&self.bytes_per_sector.to_le_bytes());
+        image[13] = sectors_per_cluster;
+        image[14..16].copy_from_slice(&1u16.to_le_bytes());
+        image[16] = self.num_fats;
+        image[17..19].copy_from_slice(&self.root_entry_count.to_le_bytes());
+        image[19..21].copy_from_slice(&total_sectors.to_le_bytes());
+        image[21] = 0xF8;
+        image[22..24].copy_from_slice(&sectors_per_fat.to_le_bytes());
+        image[24..26].copy_from_slice(&32u16.to_le_bytes());
+        image[26..28].copy_from_slice(&64u16.to_le_bytes());
+        image[32..36].copy_from_slice(&large_sectors.to_le_bytes());
+        image[36] = 0x80;
+        image[38] = 0x29;
+        image[39..43].copy_from_slice(&self.volume_id.to_le_bytes());
+        image[43..54].copy_from_slice(&self.volume_label);
+        image[54..62].copy_from_slice(b"FAT16   ");
+        image[510..512].copy_from_slice(&[0x55, 0xAA]);
+
+        // FAT の予約エントリ (0: メディア記述子, 1: 終端)
+        for fat_id in 0..self.num_fats as usize {
+            let head = (1 + fat_id * sectors_per_fat as usize) * bytes_per_sector as usize;
+            image[head..head + 2].copy_from_slice(&0xFFF8u16.to_le_bytes());
+            image[head + 2..head + 4].copy_from_slice(&0xFFFFu16.to_le_bytes());
         }
 
-        Ok(&self.clusters[head..head + bytes_per_cluster])
+        // ルートディレクトリとデータ領域はゼロのまま
+        Ok(image)
     }
 
-    fn find_dir_entry(&self, path: &Path) -> Result<Fat16DirEntry, Box<dyn StdError>> {
-        // path にマッチする DirEntry を探す
-        let dirs = path.parse();
+    fn fat_layout(&self, sectors_per_cluster: u8) -> (u16, u32) {
+        // FAT のサイズは FAT 領域を除いたセクタ数から多めに見積もる
+        let bytes_per_sector = self.bytes_per_sector as u32;
+        let root_dir_sectors = (self.root_entry_count as u32 * 32).div_ceil(bytes_per_sector);
+        let rest_sectors = self.total_sectors.saturating_sub(1 + root_dir_sectors);
+        let max_clusters = rest_sectors / sectors_per_cluster as u32;
+        let sectors_per_fat = max_clusters.saturating_add(2).saturating_mul(2).div_ceil(bytes_per_sector).min(u16::MAX as u32);
+
+        // FAT 領域を除いた実際のクラスタ数
+        let data_sectors = rest_sectors.saturating_sub(self.num_fats as u32 * sectors_per_fat);
+        (sectors_per_fat as u16, data_sectors / sectors_per_cluster as u32)
+    }
+}
 
-        let mut entry = self.root_dir.clone();
-        for dir in &dirs[..dirs.len()-1] {
-            let d = entry
-                .iter()
-                .find(|e| &e.name.to_ascii_lowercase() == dir)
-                .ok_or("No such file or direcotry")?;
-            entry = self.read_dir_entry(d)?;
+#[derive(Debug)]
+pub struct Mbr {
+    // Boot Code (446bytes)
+    pub boot_code: [u8; 446],
+    // Partition Table (16bytes x 4)
+    pub partitions: [MbrPartition; 4],
+    // Boot Signature (2bytes)
+    pub signature: [u8; 2],
+}
+
+impl Mbr {
+    pub fn parse(bytes: &[u8]) -> ParseResult<'_, Mbr> {
+        if bytes.len() < 512 {
+            return Err(format!("MBR must be 512 bytes, got {}", bytes.len()).into());
         }
 
-        entry
-            .into_iter()
-            .find(|e| &e.name.to_ascii_lowercase() == dirs[dirs.len()-1])
-            .ok_or("No such file or direcotry".into())
+        let mbr = Mbr {
+            boot_code: bytes[0..446].try_into()?,
+            partitions: [
+                MbrPartition::parse(&bytes[0x1BE..0x1CE])?,
+                MbrPartition::parse(&bytes[0x1CE..0x1DE])?,
+                MbrPartition::parse(&bytes[0x1DE..0x1EE])?,
+                MbrPartition::parse(&bytes[0x1EE..0x1FE])?,
+            ],
+            signature: bytes[510..512].try_into()?,
+        };
+        if mbr.signature != [0x55, 0xAA] {
+            return Err(format!("Invalid MBR signature: {:02x?}", mbr.signature).into());
+        }
+
+        Ok((mbr, &bytes[512..]))
     }
+}
 
-    fn read_dir_entry(&self, dir_entry: &Fat16DirEntry) -> Result<Vec<Fat16DirEntry>, Box<dyn StdError>> {
-        // FAT テーブルの参照
-        // クラスタを辿ってデータを取得
-        let bytes_per_cluster = self.bpb.bytes_per_sector as usize * self.bpb.sectors_per_cluster as usize;
-        let bytes_per_entry = 32;
-        let entries_per_cluster = (bytes_per_cluster / bytes_per_entry) as u16;
+#[derive(Debug)]
+pub struct MbrPartition {
+    // Boot Indicator (1byte)
+    pub boot_indicator: u8,
+    // Starting CHS (3bytes)
+    pub start_chs: [u8; 3],
+    // Partition Type (1byte)
+    pub partition_type: u8,
+    // Ending CHS (3bytes)
+    pub end_chs: [u8; 3],
+    // Starting LBA (4bytes)
+    pub start_lba: u32,
+    // Number of Sectors (4bytes)
+    pub sector_count: u32,
+}
 
-        let cluster_chain = self.alloc_table.get_cluster_chain(dir_entry.first_cluster as u16);
-        let mut dirs = Vec::new();
-        for cluster_number in cluster_chain {
-            let cluster_data = self.read_cluster(cluster_number)?;
-            let (part_of_dirs, _) = Fat16DirEntry::parses(cluster_data, entries_per_cluster)?;
-            dirs.extend(part_of_dirs);
-        }
+impl MbrPartition {
+    pub fn parse(bytes: &[u8]) -> Result<MbrPartition, Box<dyn StdError>> {
+        Ok(MbrPartition {
+            boot_indicator: bytes[0],
+            start_chs: bytes[1..4].try_into()?,
+            partition_type: bytes[4],
+            end_chs: bytes[5..8].try_into()?,
+            start_lba: u32::from_le_bytes(bytes[8..12].try_into()?),
+            sector_count: u32::from_le_bytes(bytes[12..16].try_into()?),
+        })
+    }
 
-        Ok(dirs)
+    pub fn is_fat16(&self) -> bool {
+        // 0x04: FAT16 (<32MB), 0x06: FAT16B, 0x0E: FAT16B (LBA)
+        matches!(self.partition_type, 0x04 | 0x06 | 0x0E)
     }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Fat16BPB {
     // The first three bytes 'E8 3C 90' (3bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
     pub x86_jmp: [u8; 3],
     // OEM Identifier (8bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
     pub oem_name: [u8; 8],
     // Bytes per Sector (2bytes)
     pub bytes_per_sector: u16,
@@ -158,7 +2349,12 @@ pub struct Fat16BPB {
 }
 
 impl Fat16BPB {
-    pub fn parse(bytes: &[u8]) -> Result<(Fat16BPB, &[u8]), Box<dyn StdError>> {
+    pub fn parse(bytes: &[u8]) -> ParseResult<'_, Fat16BPB> {
+        // BPB はブートセクタ (512 バイト) の先頭にある
+        if bytes.len() < 512 {
+            return Err(format!("image too small: need at least 512 bytes, got {}", bytes.len()).into());
+        }
+
         let bpb = Fat16BPB {
             x86_jmp: bytes[0..3].try_into()?,
             oem_name: bytes[3..11].try_into()?,
@@ -175,11 +2371,54 @@ impl Fat16BPB {
             hidden_sectors: u32::from_le_bytes(bytes[28..32].try_into()?),
             large_sectors: u32::from_le_bytes(bytes[32..36].try_into()?),
         };
+
+        // クラスタ数 (FAT の種類の判定) の計算に使う値は、FAT の種類によらずここで検証する
+        check_bytes_per_sector(bpb.bytes_per_sector)?;
+        // クラスタあたりのセクタ数は 1 - 128 の 2 のべき乗
+        if !bpb.sectors_per_cluster.is_power_of_two() {
+            return Err(format!("Invalid sectors_per_cluster: {} (must be a power of two between 1 and 128)", bpb.sectors_per_cluster).into());
+        }
+        if !(1..=2).contains(&bpb.num_fats) {
+            return Err(format!("Invalid num_fats: {} (must be 1 or 2)", bpb.num_fats).into());
+        }
         Ok((bpb, &bytes[36..]))
     }
+
+    pub fn fat_type(&self) -> FatType {
+        // FAT32 は 16 ビットの sectors_per_fat を 0 にして拡張 BPB の 32 ビット値を使う
+        if self.sectors_per_fat == 0 {
+            return FatType::Fat32;
+        }
+        FatType::from_cluster_count(data_cluster_count(self))
+    }
+
+    pub fn oem_quirks(&self) -> OemQuirks {
+        // OEM 名からフォーマットしたツールを推定する (あくまで参考情報)
+        match self.oem_name.trim_ascii_end() {
+            // Windows 95/98
+            b"MSWIN4.0" | b"MSWIN4.1" => OemQuirks { known: true, writes_lfn: true, nt_case_flags: false },
+            // Windows NT 系 (2000 以降も含む)
+            b"MSDOS5.0" => OemQuirks { known: true, writes_lfn: true, nt_case_flags: true },
+            // Linux (dosfstools)
+            b"mkdosfs" | b"mkfs.fat" => OemQuirks { known: true, writes_lfn: true, nt_case_flags: true },
+            // MS-DOS 以前・不明
+            _ => OemQuirks::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OemQuirks {
+    // 既知の OEM 名か
+    pub known: bool,
+    // LFN エントリを書き込むツールか
+    pub writes_lfn: bool,
+    // 予約バイト (オフセット 12) を小文字フラグとして使うか
+    pub nt_case_flags: bool,
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Fat16EBPB {
     // Drive Number (1byte)
     pub drive_number: u8,
@@ -190,17 +2429,26 @@ pub struct Fat16EBPB {
     // Volume ID (4bytes)
     pub volume_id: u32,
     // Volume Label (11bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
     pub volume_label: [u8; 11],
     // File System Type (8bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
     pub file_system_type: [u8; 8],
     // Boot Code (448bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
     pub boot_code: [u8; 448],
     // Boot Partition Signature (2bytes)
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_hex"))]
     pub boot_partition_signature: [u8; 2],
 }
 
 impl Fat16EBPB {
-    pub fn parse(bytes: &[u8]) -> Result<(Fat16EBPB, &[u8]), Box<dyn StdError>> {
+    pub fn parse(bytes: &[u8]) -> ParseResult<'_, Fat16EBPB> {
+        // BPB (36 バイト) の後ろからブートセクタの末尾まで
+        if bytes.len() < 476 {
+            return Err(format!("EBPB too small: need at least 476 bytes, got {}", bytes.len()).into());
+        }
+
         let ebpb = Fat16EBPB {
             drive_number: bytes[0],
             reserved1: bytes[1],
@@ -238,80 +2486,334 @@ BPB + EBPB + FAT 領域のサイズ
 
 */
 
+// FAT の種類 (エントリのビット幅)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FatType {
+    Fat12,
+    Fat16,
+    Fat32,
+}
+
+impl FatType {
+    pub fn from_cluster_count(cluster_count: u32) -> FatType {
+        // FAT の種類はデータクラスタ数のみで決まる (file_system_type の文字列は参考情報)
+        if cluster_count < 4085 {
+            FatType::Fat12
+        } else if cluster_count < 65525 {
+            FatType::Fat16
+        } else {
+            FatType::Fat32
+        }
+    }
+
+    pub fn eoc_marker(&self) -> u32 {
+        // この値以上ならチェーンの終端
+        match self {
+            FatType::Fat12 => 0x0FF8,
+            FatType::Fat16 => 0xFFF8,
+            FatType::Fat32 => 0x0FFF_FFF8,
+        }
+    }
+
+    pub fn bad_marker(&self) -> u32 {
+        // 不良クラスタ
+        match self {
+            FatType::Fat12 => 0x0FF7,
+            FatType::Fat16 => 0xFFF7,
+            FatType::Fat32 => 0x0FFF_FFF7,
+        }
+    }
+}
+
+// FAT エントリの値が示すクラスタの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusterStatus {
+    Free,
+    // 次のクラスタ番号
+    Next(u16),
+    Reserved,
+    Bad,
+    EndOfChain,
+}
+
+impl ClusterStatus {
+    pub fn classify(value: u16, fat_type: FatType) -> ClusterStatus {
+        let value = value as u32;
+        if value == 0x0000 {
+            ClusterStatus::Free
+        } else if value >= fat_type.eoc_marker() {
+            ClusterStatus::EndOfChain
+        } else if value == fat_type.bad_marker() {
+            ClusterStatus::Bad
+        } else if value == 0x0001 || value > fat_type.bad_marker() - 8 {
+            // 0x0001 と 0xFFF0 - 0xFFF6 は予約
+            ClusterStatus::Reserved
+        } else {
+            ClusterStatus::Next(value as u16)
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Fat16AllocTable {
     table: Vec<u16>,
+    // 2 つ目以降の FAT (ミラー)
+    mirrors: Vec<Vec<u16>>,
+    // 各 FAT のエントリより後ろの生のバイト列 (書き出し時にそのまま戻す)
+    tails: Vec<Vec<u8>>,
+    // 次に空きクラスタを探し始める位置
+    next_free: u16,
+    fat_type: FatType,
+    // 先頭クラスタ -> クラスタチェーン のキャッシュ (書き込みで破棄する)
+    chain_cache: RefCell<BTreeMap<u16, Vec<u16>>>,
 }
 
 impl Fat16AllocTable {
-    pub fn parse<'a>(bytes: &'a [u8], bpb: &Fat16BPB) -> Result<(Fat16AllocTable, &'a [u8]), Box<dyn StdError>> {
+    pub fn parse<'a>(bytes: &'a [u8], bpb: &Fat16BPB) -> ParseResult<'a, Fat16AllocTable> {
         // u32 キャスト
         let num_fats = bpb.num_fats as u32;
         let sectors_per_fat = bpb.sectors_per_fat as u32;
         let bytes_per_sector = bpb.bytes_per_sector as u32;
-        let total_sectors = bpb.total_sectors as u32;
-        let sectors_per_cluster = bpb.sectors_per_cluster as u32;
+
+        // BPB の値が FAT16 として妥当か
+        if sectors_per_fat == 0 {
+            return Err("Invalid sectors_per_fat: 0".into());
+        }
 
         // 領域サイズなどを計算
         let fat_size = num_fats * sectors_per_fat * bytes_per_sector;
 
-        let fat_entry_cnt = if total_sectors == 0 { // total_sectors が 0 の場合、セクタ数は65536以上。 large_sectors を使う
-            bpb.large_sectors / sectors_per_cluster
-        } else {
-            total_sectors / sectors_per_cluster
+        // データクラスタ数 + 予約エントリ (0, 1)
+        // 予約領域・FAT 領域・ルートディレクトリ領域を除いたセクタから数える (total_sectors が 0 なら large_sectors)
+        let fat_entry_cnt = data_cluster_count(bpb) + 2;
+        // FAT12 は 1 エントリ 12 ビット、それ以外はこのパーサでは 16 ビットとして読む
+        let fat_type = match bpb.fat_type() {
+            FatType::Fat12 => FatType::Fat12,
+            _ => FatType::Fat16,
+        };
+
+        // 1 つの FAT に収まるエントリ数を超えない (超えた分は次の FAT を読んでしまう)
+        let fat_entry_cnt = match fat_type {
+            FatType::Fat12 => fat_entry_cnt.min(sectors_per_fat * bytes_per_sector * 2 / 3),
+            _ => fat_entry_cnt.min(sectors_per_fat * bytes_per_sector / 2),
+        };
+
+        // FAT 領域が切り詰められていないか
+        if bytes.len() < fat_size as usize {
+            return Err(format!("FAT region is truncated. expected = {} bytes, len = {}", fat_size, bytes.len()).into());
+        }
+
+        // FAT エントリを読み込み (FAT の数だけ)
+        let mut tables = vec![];
+        let mut tails = vec![];
+        let tail_offset = match fat_type {
+            FatType::Fat12 => fat_entry_cnt as usize * 3 / 2,
+            _ => fat_entry_cnt as usize * 2,
         };
+        for fat_id in 0..num_fats {
+            let head = (fat_id * sectors_per_fat * bytes_per_sector) as usize;
+            tails.push(bytes[head + tail_offset..head + (sectors_per_fat * bytes_per_sector) as usize].to_vec());
+            let mut table = vec![];
+            for id in 0..fat_entry_cnt {
+                // FAT12 は 2 エントリを 3 バイトに詰めている
+                let offset = match fat_type {
+                    FatType::Fat12 => head + (id + id / 2) as usize,
+                    _ => head + (id * 2) as usize,
+                };
+                let entry = bytes
+                    .get(offset..offset + 2)
+                    .ok_or_else(|| format!("FAT entry out of range. fat = {}, entry = {}, len = {}", fat_id, id, bytes.len()))?;
+                let entry = u16::from_le_bytes(entry.try_into()?);
+                table.push(match fat_type {
+                    FatType::Fat12 if id % 2 == 0 => entry & 0x0FFF,
+                    FatType::Fat12 => entry >> 4,
+                    _ => entry,
+                });
+            }
+            tables.push(table);
+        }
+        let mirrors = tables.split_off(1.min(tables.len()));
+        let table = tables.pop().unwrap_or_default();
+
+        Ok((Fat16AllocTable { table, mirrors, tails, next_free: 2, fat_type, chain_cache: RefCell::default() }, &bytes[fat_size as usize..]))
+    }
+
+    pub fn to_bytes(&self, bpb: &Fat16BPB) -> Vec<u8> {
+        // 1 つ目の FAT とミラーを sectors_per_fat セクタずつ並べる
+        let fat_bytes = bpb.sectors_per_fat as usize * bpb.bytes_per_sector as usize;
+        let mut bytes = vec![0u8; fat_bytes * (1 + self.mirrors.len())];
+        let tables = core::iter::once(&self.table).chain(&self.mirrors);
+        for ((fat, table), tail) in bytes.chunks_mut(fat_bytes).zip(tables).zip(&self.tails) {
+            // クラスタに対応しない末尾は読み込んだ時のまま
+            let tail_head = fat_bytes - tail.len();
+            fat[tail_head..].copy_from_slice(tail);
+            for (id, entry) in table.iter().enumerate() {
+                match self.fat_type {
+                    // FAT12 は 2 エントリを 3 バイトに詰める
+                    FatType::Fat12 => {
+                        let offset = id + id / 2;
+                        let packed = u16::from_le_bytes([fat[offset], fat[offset + 1]]);
+                        let packed = if id % 2 == 0 {
+                            (packed & 0xF000) | (entry & 0x0FFF)
+                        } else {
+                            (packed & 0x000F) | (entry << 4)
+                        };
+                        fat[offset..offset + 2].copy_from_slice(&packed.to_le_bytes());
+                    }
+                    _ => fat[id * 2..id * 2 + 2].copy_from_slice(&entry.to_le_bytes()),
+                }
+            }
+        }
+        bytes
+    }
+
+    pub fn fat_type(&self) -> FatType {
+        self.fat_type
+    }
+
+    pub fn entry_count(&self) -> usize {
+        // 読み込んだ FAT エントリ数 (予約エントリ 0, 1 を含む)
+        self.table.len()
+    }
+
+    pub fn set_entry(&mut self, cluster: u16, value: u16) {
+        // ミラーも含めて全ての FAT を更新する
+        self.chain_cache.get_mut().clear();
+        self.table[cluster as usize] = value;
+        for mirror in &mut self.mirrors {
+            mirror[cluster as usize] = value;
+        }
+    }
+
+    pub fn entry(&self, cluster: u16) -> Option<u16> {
+        // FAT エントリの生の値 (範囲外は None)
+        self.table.get(cluster as usize).copied()
+    }
+
+    pub fn cluster_status(&self, cluster: u16) -> Option<ClusterStatus> {
+        self.entry(cluster).map(|value| ClusterStatus::classify(value, self.fat_type))
+    }
+
+    pub fn next_free_hint(&self) -> Option<u16> {
+        // カーソル位置から末尾まで探し、見つからなければ先頭に戻って探す
+        let cursor = (self.next_free as usize).clamp(2, self.table.len());
+        (cursor..self.table.len())
+            .chain(2..cursor)
+            .find(|idx| self.table[*idx] == 0x0000)
+            .map(|idx| idx as u16)
+    }
+
+    pub fn allocate_cluster(&mut self) -> Option<u16> {
+        // 空きクラスタを探して終端 (0xFFFF) としてマークする
+        let cluster = self.next_free_hint()?;
+        self.set_entry(cluster, 0xFFFF);
+        self.next_free = cluster.wrapping_add(1);
+        Some(cluster)
+    }
+
+    pub fn free_chain(&mut self, start_cluster: u16) {
+        // チェーン上のクラスタを全て空きにする (壊れたチェーンは辿れるところまで)
+        // 空きにしたクラスタに戻ってきた場合は次が 0 になるので、循環していても止まる
+        let mut cluster = start_cluster;
+        while cluster >= 2 && (cluster as usize) < self.table.len() && (cluster as u32) < self.fat_type.bad_marker() {
+            let next = self.table[cluster as usize];
+            self.set_entry(cluster, 0x0000);
+            cluster = next;
+        }
+        self.next_free = 2;
+    }
+
+    pub fn verify_mirror(&self) -> Option<u16> {
+        // 1 つ目の FAT とミラーが食い違う最初のクラスタ番号
+        self.mirrors
+            .iter()
+            .filter_map(|mirror| {
+                self.table
+                    .iter()
+                    .zip(mirror)
+                    .position(|(a, b)| a != b)
+            })
+            .min()
+            .map(|cluster| cluster as u16)
+    }
 
-        // FAT エントリを読み込み
-        let mut table = vec![];
-        for id in 0..fat_entry_cnt {
-            let offset = (id * 2) as usize;
-            let entry = u16::from_le_bytes(bytes[offset..offset+2].try_into()?);
-            table.push(entry);
+    pub fn get_cluster_chain(&self, start_cluster: u16) -> Result<Vec<u16>, ChainError> {
+        // 一度辿ったチェーンはキャッシュから返す
+        if let Some(chain) = self.chain_cache.borrow().get(&start_cluster) {
+            return Ok(chain.clone());
         }
 
-        Ok((Fat16AllocTable { table }, &bytes[fat_size as usize..]))
+        // 範囲外・循環・未使用・不良クラスタを検査しながら辿る (先頭クラスタ 0 は空のチェーン)
+        let chain = self.get_checked_cluster_chain(start_cluster)?;
+        self.chain_cache.borrow_mut().insert(start_cluster, chain.clone());
+        Ok(chain)
     }
 
-    pub fn get_cluster_chain(&self, start_cluster: u16) -> Vec<u16> {
+    pub fn get_checked_cluster_chain(&self, start_cluster: u16) -> Result<Vec<u16>, ChainError> {
+        if start_cluster == 0 {
+            return Ok(vec![]);
+        }
+
         let mut chain = vec![];
+        let mut visited = vec![false; self.table.len()];
         let mut cluster = start_cluster;
 
-        while cluster < 0xFFF8 {
+        while (cluster as u32) < self.fat_type.eoc_marker() {
+            // 範囲外・循環のチェック
+            if cluster < 2 || cluster as usize >= self.table.len() {
+                return Err(ChainError::OutOfRange(cluster));
+            }
+            if visited[cluster as usize] {
+                return Err(ChainError::Cycle(cluster));
+            }
+            visited[cluster as usize] = true;
             chain.push(cluster);
-            cluster = self.table[cluster as usize];
+
+            // 不良・未使用クラスタのチェック
+            let next = self.table[cluster as usize];
+            if next == 0x0000 {
+                return Err(ChainError::FreeCluster(cluster));
+            }
+            if next as u32 == self.fat_type.bad_marker() {
+                return Err(ChainError::BadCluster(cluster));
+            }
+            cluster = next;
         }
 
-        chain
+        Ok(chain)
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Fat16DirEntry {
-    name: String,
-    attribute: u8,
-    reserved: u8,
-    creation_time: Fat16Time,
-    creation_date: Fat16Date,
-    last_access_date: Fat16Date,
-    last_modify_time: Fat16Time,
-    last_modify_date: Fat16Date,
-    first_cluster: u32,
-    file_size: u32,
+    pub name: String,
+    // 8.3 形式の名前 (LFN がない場合は name と同じ)
+    pub short_name: String,
+    pub attribute: u8,
+    pub reserved: u8,
+    pub creation_time: Fat16Time,
+    pub creation_date: Fat16Date,
+    pub last_access_date: Fat16Date,
+    pub last_modify_time: Fat16Time,
+    pub last_modify_date: Fat16Date,
+    pub first_cluster: u32,
+    pub file_size: u32,
 }
 
+// 属性ビットと表示用の文字
+const ATTRIBUTE_FLAGS: [(u8, char); 6] = [(0x01, 'R'), (0x02, 'H'), (0x04, 'S'), (0x08, 'V'), (0x10, 'D'), (0x20, 'A')];
+
 impl Display for Fat16DirEntry {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // ファイル名
         write!(f, "{}", self.name)?;
 
         // 属性
         write!(f, " (attr: ")?;
-        if self.attribute & 0x01 != 0 { write!(f, "R")?; }
-        if self.attribute & 0x02 != 0 { write!(f, "H")?; }
-        if self.attribute & 0x04 != 0 { write!(f, "S")?; }
-        if self.attribute & 0x08 != 0 { write!(f, "V")?; }
-        if self.attribute & 0x10 != 0 { write!(f, "D")?; }
-        if self.attribute & 0x20 != 0 { write!(f, "A")?; }
+        for (mask, flag) in ATTRIBUTE_FLAGS {
+            if self.attribute & mask != 0 { write!(f, "{}", flag)?; }
+        }
         write!(f, ", ")?;
 
         // その他
@@ -320,20 +2822,101 @@ impl Display for Fat16DirEntry {
 }
 
 impl Fat16DirEntry {
-    pub fn parses(bytes: &[u8], num_entry: u16) -> Result<(Vec<Fat16DirEntry>, &[u8]), Box<dyn StdError>> {
+    pub fn is_dir(&self) -> bool {
+        self.attribute & 0x10 != 0
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.attribute & 0x02 != 0
+    }
+
+    pub fn is_system(&self) -> bool {
+        self.attribute & 0x04 != 0
+    }
+
+    pub fn is_volume_label(&self) -> bool {
+        self.attribute & 0x08 != 0
+    }
+
+    pub fn matches(&self, lower_name: &str) -> bool {
+        // 長い名前・8.3 形式の名前のどちらでも一致とみなす (大文字・小文字は区別しない)
+        self.name.to_ascii_lowercase() == lower_name || self.short_name.to_ascii_lowercase() == lower_name
+    }
+
+    pub fn is_dot(&self) -> bool {
+        // . と .. (自身と親ディレクトリ)
+        !self.name.is_empty() && self.name.chars().all(|c| c == '.')
+    }
+
+    #[cfg(feature = "std")]
+    pub fn touch(&mut self, now: SystemTime) {
+        // 更新日時と最終アクセス日を now にする (FAT の精度に合わせて 2 秒単位に切り捨てる)
+        let (date, time) = Fat16Date::from_system_time(now);
+        self.last_access_date = date.clone();
+        self.last_modify_date = date;
+        self.last_modify_time = time;
+    }
+
+    pub fn format_long(&self) -> String {
+        // ls -l 風の固定幅の列: 属性 (立っていないものは '-')、サイズ (右詰め)、更新日時、名前
+        let flags = ATTRIBUTE_FLAGS
+            .iter()
+            .map(|(mask, flag)| if self.attribute & mask != 0 { *flag } else { '-' })
+            .collect::<String>();
+        let (date, time) = (&self.last_modify_date, &self.last_modify_time);
+        format!(
+            "{} {:>10} {:04}-{:02}-{:02} {:02}:{:02}:{:02} {}",
+            flags, self.file_size, date.year, date.month, date.day, time.hour, time.minute, time.second, self.name,
+        )
+    }
+
+    pub fn parses(bytes: &[u8], num_entry: u16) -> ParseResult<'_, Vec<Fat16DirEntry>> {
+        Self::parses_inner(bytes, num_entry, false, false)
+    }
+
+    pub fn parses_with_deleted(bytes: &[u8], num_entry: u16) -> ParseResult<'_, Vec<Fat16DirEntry>> {
+        // 削除済み (0xE5) のエントリも先頭バイトを '_' に戻して返す
+        Self::parses_inner(bytes, num_entry, true, false)
+    }
+
+    pub fn parses_until_terminator(bytes: &[u8], num_entry: u16) -> ParseResult<'_, Vec<Fat16DirEntry>> {
+        // 先頭バイトが 0x00 のエントリ以降は全て未使用なので読まない (残りは num_entry 分進めた位置)
+        Self::parses_inner(bytes, num_entry, false, true)
+    }
+
+    fn parses_inner(bytes: &[u8], num_entry: u16, with_deleted: bool, stop_at_terminator: bool) -> ParseResult<'_, Vec<Fat16DirEntry>> {
         let mut entries = vec![];
 
         if num_entry as usize * 32 > bytes.len() {
-            return Err(format!("'bytes' must be larger than {}.", num_entry * 32).into());
+            return Err(format!("'bytes' must be larger than {}.", num_entry as usize * 32).into());
         }
 
         let mut dir_bytes = &bytes[0..(num_entry as usize * 32)];
-        while dir_bytes.len() > 0 {
-            match Fat16DirEntry::parse_entry(&dir_bytes)? {
+        while !dir_bytes.is_empty() {
+            if stop_at_terminator && dir_bytes[0] == 0x00 {
+                break;
+            }
+
+            // 削除済みの LFN は読み飛ばし、SFN のみ復元する
+            if with_deleted && dir_bytes[0] == 0xE5 {
+                if dir_bytes[11] != 0x0f {
+                    let mut record: [u8; 32] = dir_bytes[..32].try_into()?;
+                    record[0] = b'_';
+                    if let (Some(entry), _) = Fat16DirEntry::parse_sfn(&record)? {
+                        entries.push(entry);
+                    }
+                }
+                dir_bytes = &dir_bytes[32..];
+                continue;
+            }
+
+            match Fat16DirEntry::parse_entry(dir_bytes)? {
                 (Some(entry), rest) => {
                     entries.push(entry);
                     dir_bytes = rest;
                 },
+                // SFN が続かない LFN は読み飛ばし、その後ろのレコードから続ける
+                (None, rest) if rest.len() < dir_bytes.len() => dir_bytes = rest,
                 (None, _) => dir_bytes = &dir_bytes[32..],
             }
         }
@@ -341,12 +2924,19 @@ impl Fat16DirEntry {
         Ok((entries, &bytes[(num_entry as usize * 32)..]))
     }
 
-    pub fn parse_entry(bytes: &[u8]) -> Result<(Option<Fat16DirEntry>, &[u8]), Box<dyn StdError>> {
+    pub fn parse_entry(bytes: &[u8]) -> ParseResult<'_, Option<Fat16DirEntry>> {
         // LFN エントリのパース
         let (lfn_name, bytes) = Self::parse_lfn(bytes)?;
 
         // SFN (8.3形式) エントリのパース
+        // LFN のチェックサムが SFN の名前と一致しない場合は SFN の名前を使う
+        let short_name: Option<[u8; 11]> = bytes.get(0..11).map(|name| name.try_into()).transpose()?;
+        let lfn_name = lfn_name
+            .filter(|(_, checksum)| checksum.is_some() && *checksum == short_name.as_ref().map(lfn::checksum))
+            .map(|(lfn_name, _)| lfn_name);
         let (entry, bytes) = Self::parse_sfn(bytes)?;
+
+        // LFN の後ろに有効な SFN がなければ LFN は捨てる (LFN の分だけ読み進めた位置を返す)
         let entry = match (entry, lfn_name) {
             (Some(mut entry), Some(lfn_name)) => {
                 entry.name = lfn_name;
@@ -358,21 +2948,31 @@ impl Fat16DirEntry {
         Ok((entry, bytes))
     }
 
-    fn parse_sfn(bytes: &[u8]) -> Result<(Option<Fat16DirEntry>, &[u8]), Box<dyn StdError>> {
+    fn parse_sfn(bytes: &[u8]) -> ParseResult<'_, Option<Fat16DirEntry>> {
         // 有効エントリの判定
-        if bytes[0] == 0x00 || bytes[0] == 0xE5 {
-            if bytes[0] == 0xE5 {
-                println!("this is removed entry!");
-            }
+        if bytes.is_empty() || bytes[0] == 0x00 {
+            return Ok((None, bytes));
+        }
+        if bytes[0] == 0xE5 { // 削除済み
             return Ok((None, bytes));
         }
 
+        // 先頭が 0xE5 の名前は削除マークと区別するため 0x05 で格納されている
+        let mut name_bytes: [u8; 8] = bytes[0..8].try_into()?;
+        if name_bytes[0] == 0x05 {
+            name_bytes[0] = 0xE5;
+        }
+
+        // 拡張子が空の場合は '.' を付けない
+        let base = cp437::decode(&name_bytes);
+        let ext = cp437::decode(&bytes[8..11]);
+        let (base, ext) = (base.trim_end_matches(' '), ext.trim_end_matches(' '));
+        let name = if ext.is_empty() { base.to_string() } else { format!("{}.{}", base, ext) };
+
         // SFN エントリの読み込み
         let entry = Fat16DirEntry {
-            name: format!("{}.{}",
-                String::from_utf8_lossy(&bytes[0..8]).trim(),
-                String::from_utf8_lossy(&bytes[8..11]).trim(),
-            ),
+            short_name: name.clone(),
+            name,
             attribute: bytes[11],
             reserved: bytes[12],
             creation_time: Fat16Time::from((u16::from_le_bytes(bytes[14..16].try_into()?), bytes[13])),
@@ -380,7 +2980,9 @@ impl Fat16DirEntry {
             last_access_date: Fat16Date::from(u16::from_le_bytes(bytes[18..20].try_into()?)),
             last_modify_time: Fat16Time::from(u16::from_le_bytes(bytes[22..24].try_into()?)),
             last_modify_date: Fat16Date::from(u16::from_le_bytes(bytes[24..26].try_into()?)),
-            first_cluster: u16::from_le_bytes(bytes[26..28].try_into()?) as u32            ,
+            // 上位 16 ビット (FAT32 のみ) と下位 16 ビット
+            first_cluster: (u16::from_le_bytes(bytes[20..22].try_into()?) as u32) << 16
+                | u16::from_le_bytes(bytes[26..28].try_into()?) as u32,
             file_size: u32::from_le_bytes(bytes[28..32].try_into()?),
         };
         let bytes = &bytes[32..];
@@ -390,16 +2992,30 @@ impl Fat16DirEntry {
 
     // READ_ONLY=0x01 HIDDEN=0x02 SYSTEM=0x04 VOLUME_ID=0x08 DIRECTORY=0x10 ARCHIVE=0x20
     // LFN=READ_ONLY|HIDDEN|SYSTEM|VOLUME_ID
-    fn parse_lfn(bytes: &[u8]) -> Result<(Option<String>, &[u8]), Box<dyn StdError>> {
+    // チェックサムは全ての LFN エントリで一致している場合のみ Some
+    fn parse_lfn(bytes: &[u8]) -> ParseResult<'_, Option<(String, Option<u8>)>> {
         // LFN 判定
-        if bytes[11] != 0x0f {
+        if bytes.is_empty() || bytes[11] != 0x0f {
             return Ok((None, bytes));
         }
 
         // LFN エントリが続く限り読み進める
         let mut bytes = bytes;
-        let mut text = "".to_string();
-        while bytes[11] == 0x0f {
+        let mut units: Vec<u16> = vec![];
+        let mut checksum = Some(bytes[13]);
+        let mut valid = bytes[0] & 0x40 != 0;   // 先頭は最後の断片 (0x40) である必要がある
+        let mut expected_seq = bytes[0] & 0x1f;
+        while !bytes.is_empty() && bytes[11] == 0x0f {
+            if checksum != Some(bytes[13]) {
+                checksum = None;
+            }
+
+            // シーケンス番号は 1 まで 1 つずつ減っていく
+            if bytes[0] & 0x1f != expected_seq || expected_seq == 0 {
+                valid = false;
+            }
+            expected_seq = expected_seq.saturating_sub(1);
+
             // 文字列部分の抜き取り
             let text_bytes = [
                 u16::from_le_bytes(bytes[1..3].try_into()?),    // 1文字目
@@ -416,23 +3032,101 @@ impl Fat16DirEntry {
                 u16::from_le_bytes(bytes[28..30].try_into()?),  // 12文字目
                 u16::from_le_bytes(bytes[30..32].try_into()?),  // 13文字目
             ];
-            text = String::from_utf16(&text_bytes)? + &text;
+            // サロゲートペアが断片をまたぐことがあるので、UTF-16 のまま連結する
+            units.splice(0..0, text_bytes);
 
             // 読み進める
             bytes = &bytes[32..];
         }
 
+        // 不完全な LFN は無視する (SFN の名前を使う)
+        if !valid || expected_seq != 0 {
+            return Ok((None, bytes));
+        }
+
         // ヌル終端の除去
-        let text = text.find('\0')
-            .map(|idx| &text[..idx])
-            .unwrap_or(&text)
-            .to_string();
+        // 対になっていないサロゲートを含む場合は不完全な LFN と同様に無視する
+        let len = units.iter().position(|unit| *unit == 0x0000).unwrap_or(units.len());
+        let Ok(text) = String::from_utf16(&units[..len]) else {
+            return Ok((None, bytes));
+        };
 
-        Ok((Some(text), bytes))
+        Ok((Some((text, checksum)), bytes))
     }
 }
 
-#[derive(Debug, Clone)]
+#[cfg(feature = "std")]
+fn current_timestamp() -> Option<(Fat16Date, Fat16Time)> {
+    Some(Fat16Date::from_system_time(SystemTime::now()))
+}
+
+#[cfg(not(feature = "std"))]
+fn current_timestamp() -> Option<(Fat16Date, Fat16Time)> {
+    // 時計のない no_std 環境では現在時刻が分からない
+    None
+}
+
+fn sfn_record(short_name: &[u8; 11], attribute: u8, first_cluster: u16, file_size: u32) -> [u8; 32] {
+    // 作成・更新日時は write_file と同じく現在時刻 (分からなければ 1980/01/01 00:00:00)
+    let (date, time) = current_timestamp().unwrap_or((Fat16Date::from(0x0021), Fat16Time::from(0)));
+
+    let mut record = [0u8; 32];
+    record[0..11].copy_from_slice(short_name);
+    record[11] = attribute;
+    record[14..16].copy_from_slice(&time.to_u16().0.to_le_bytes());
+    record[16..18].copy_from_slice(&date.to_u16().to_le_bytes());
+    set_record_modified(&mut record, &date, &time);
+    record[26..28].copy_from_slice(&first_cluster.to_le_bytes());
+    record[28..32].copy_from_slice(&file_size.to_le_bytes());
+    record
+}
+
+fn set_record_modified(record: &mut [u8; 32], date: &Fat16Date, time: &Fat16Time) {
+    // 更新日時と最終アクセス日
+    record[18..20].copy_from_slice(&date.to_u16().to_le_bytes());
+    record[22..24].copy_from_slice(&time.to_u16().0.to_le_bytes());
+    record[24..26].copy_from_slice(&date.to_u16().to_le_bytes());
+}
+
+fn set_record_fields(record: &mut [u8; 32], entry: &Fat16DirEntry) {
+    // 名前 (0..11) 以外のフィールド
+    let (creation_time, creation_tenths) = entry.creation_time.to_u16();
+    record[11] = entry.attribute;
+    record[12] = entry.reserved;
+    record[13] = creation_tenths;
+    record[14..16].copy_from_slice(&creation_time.to_le_bytes());
+    record[16..18].copy_from_slice(&entry.creation_date.to_u16().to_le_bytes());
+    record[18..20].copy_from_slice(&entry.last_access_date.to_u16().to_le_bytes());
+    record[20..22].copy_from_slice(&((entry.first_cluster >> 16) as u16).to_le_bytes());
+    record[22..24].copy_from_slice(&entry.last_modify_time.to_u16().0.to_le_bytes());
+    record[24..26].copy_from_slice(&entry.last_modify_date.to_u16().to_le_bytes());
+    record[26..28].copy_from_slice(&(entry.first_cluster as u16).to_le_bytes());
+    record[28..32].copy_from_slice(&entry.file_size.to_le_bytes());
+}
+
+fn find_free_slots(stream: &[u8], count: usize, stop_at_terminator: bool) -> Option<usize> {
+    // 未使用 (0x00) または削除済み (0xE5) のスロットが count 個連続する位置
+    let mut run = 0;
+    for (idx, record) in stream.chunks(32).enumerate() {
+        // 終端 (0x00) 以降は全て空きとみなす (読み込み側が打ち切るので、それより後ろには書かない)
+        if stop_at_terminator && record[0] == 0x00 {
+            let head = idx - run;
+            return (head + count <= stream.len() / 32).then_some(head);
+        }
+        if record[0] == 0x00 || record[0] == 0xE5 {
+            run += 1;
+            if run == count {
+                return Some(idx + 1 - count);
+            }
+        } else {
+            run = 0;
+        }
+    }
+    None
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Fat16Date {
     pub year: u16,
     pub month: u8,
@@ -449,7 +3143,84 @@ impl From<u16> for Fat16Date {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Fat16Date {
+    pub fn checked(date: u16) -> Result<Fat16Date, Box<dyn StdError>> {
+        // From と異なり、範囲外の月・日はエラーにする
+        let decoded = Fat16Date::from(date);
+        if !(1..=12).contains(&decoded.month) || !(1..=31).contains(&decoded.day) {
+            return Err(format!("Invalid date: {:#06x} (month = {}, day = {})", date, decoded.month, decoded.day).into());
+        }
+        Ok(decoded)
+    }
+
+    pub fn duration_since_epoch(&self) -> Duration {
+        // 1970/01/01 からの日数 (グレゴリオ暦, 3 月始まりで計算)
+        let month = self.month.clamp(1, 12) as u64;
+        let day = self.day.max(1) as u64;
+        let year = self.year as u64 - if month <= 2 { 1 } else { 0 };
+        let era = year / 400;
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+
+        Duration::from_secs(days * 24 * 60 * 60)
+    }
+
+    pub fn to_u16(&self) -> u16 {
+        // 年は 1980 - 2107 に丸める
+        let year = self.year.clamp(1980, 2107) - 1980;
+        (year << 9) | ((self.month as u16 & 0x0F) << 5) | (self.day as u16 & 0x1F)
+    }
+
+    pub fn from_duration_since_epoch(duration: Duration) -> (Fat16Date, Fat16Time) {
+        // 1970/01/01 からの日数を年月日に変換する (グレゴリオ暦, 3 月始まりで計算)
+        let secs = duration.as_secs();
+        let days = secs / (24 * 60 * 60) + 719468;
+        let era = days / 146097;
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        // FAT で表せない範囲は 1980/01/01 00:00:00 - 2107/12/31 23:59:58 に丸める
+        if year < 1980 {
+            return (Fat16Date::from(0x0021), Fat16Time::from(0));
+        }
+        if year > 2107 {
+            return (Fat16Date::from(0xFF9F), Fat16Time::from(0xBF7D));
+        }
+
+        // 秒は 2 秒単位に切り捨てる
+        let secs_of_day = secs % (24 * 60 * 60);
+        let date = Fat16Date { year: year as u16, month: month as u8, day: day as u8 };
+        let time = Fat16Time {
+            hour: (secs_of_day / 3600) as u8,
+            minute: (secs_of_day / 60 % 60) as u8,
+            second: (secs_of_day % 60 / 2 * 2) as u8,
+            tenths_of_second: 0,
+        };
+        (date, time)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_system_time(time: SystemTime) -> (Fat16Date, Fat16Time) {
+        // FAT はタイムゾーンを持たないので UTC として扱う
+        Fat16Date::from_duration_since_epoch(time.duration_since(UNIX_EPOCH).unwrap_or_default())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn to_system_time(&self, time: &Fat16Time) -> SystemTime {
+        // FAT はタイムゾーンを持たないので UTC として扱う
+        UNIX_EPOCH + self.duration_since_epoch() + time.duration_since_midnight()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Fat16Time {
     pub hour: u8,
     pub minute: u8,
@@ -473,3 +3244,63 @@ impl From<(u16, u8)> for Fat16Time {
         }
     }
 }
+
+impl Fat16Time {
+    pub fn to_u16(&self) -> (u16, u8) {
+        // 作成時刻の 10 ミリ秒単位の補正は別のバイト (From<(u16, u8)> の逆)
+        let time = ((self.hour as u16 & 0x1F) << 11) | ((self.minute as u16 & 0x3F) << 5) | ((self.second as u16 / 2) & 0x1F);
+        (time, self.tenths_of_second)
+    }
+
+    pub fn checked(time: u16) -> Result<Fat16Time, Box<dyn StdError>> {
+        // From と異なり、範囲外の時・分・秒はエラーにする
+        let decoded = Fat16Time::from(time);
+        if decoded.hour >= 24 || decoded.minute >= 60 || decoded.second >= 60 {
+            return Err(format!(
+                "Invalid time: {:#06x} (hour = {}, minute = {}, second = {})",
+                time, decoded.hour, decoded.minute, decoded.second,
+            ).into());
+        }
+        Ok(decoded)
+    }
+
+    pub fn duration_since_midnight(&self) -> Duration {
+        // 秒は 2 秒単位, tenths_of_second は 10 ミリ秒単位 (0 - 199) の補正
+        let secs = self.hour as u64 * 60 * 60 + self.minute as u64 * 60 + self.second as u64;
+        Duration::from_secs(secs) + Duration::from_millis(self.tenths_of_second as u64 * 10)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<&Fat16Date> for chrono::NaiveDate {
+    fn from(date: &Fat16Date) -> chrono::NaiveDate {
+        // 不正な日付は FAT のエポック (1980/01/01) とする
+        chrono::NaiveDate::from_ymd_opt(date.year as i32, date.month as u32, date.day as u32)
+            .or(chrono::NaiveDate::from_ymd_opt(1980, 1, 1))
+            .unwrap()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<&Fat16Time> for chrono::NaiveTime {
+    fn from(time: &Fat16Time) -> chrono::NaiveTime {
+        // tenths_of_second は 10 ミリ秒単位 (0 - 199) の補正
+        let millis = time.tenths_of_second as u32 * 10;
+        chrono::NaiveTime::from_hms_milli_opt(time.hour as u32, time.minute as u32, time.second as u32 + millis / 1000, millis % 1000)
+            .unwrap_or(chrono::NaiveTime::MIN)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Fat16DirEntry {
+    pub fn modified(&self) -> chrono::NaiveDateTime {
+        chrono::NaiveDateTime::new((&self.last_modify_date).into(), (&self.last_modify_time).into())
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serialize_hex<S: serde::Serializer, T: AsRef<[u8]>>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    // バイト列は数値の配列ではなく 16 進文字列にする
+    let hex = bytes.as_ref().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+    serializer.serialize_str(&hex)
+}
@@ -68,7 +68,2927 @@ fn original_crate() -> Result<(), Box<dyn StdError>> {
     Ok(())
 }
 
+#[serial]
+#[test]
+fn broken_directory_chain() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, Fat16Error};
+
+    // /test_dir_3 の先頭クラスタの手前でイメージを切り詰める
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(&img_path)?;
+    let entry = fs.root_dir.iter().find(|e| e.name == "test_dir_3").unwrap();
+    let bytes_per_sector = fs.bpb.bytes_per_sector as u64;
+    let bytes_per_cluster = bytes_per_sector * fs.bpb.sectors_per_cluster as u64;
+    let data_offset = (fs.bpb.reserved_sector_count as u64 + fs.bpb.num_fats as u64 * fs.bpb.sectors_per_fat as u64) * bytes_per_sector
+        + fs.bpb.root_entry_count as u64 * 32;
+    let dir_offset = data_offset + (entry.first_cluster as u64 - 2) * bytes_per_cluster;
+    OpenOptions::new().write(true).open(&img_path)?.set_len(dir_offset)?;
+
+    // 壊れたディレクトリを読むとディレクトリ名付きのエラーになる
+    let fs = Fat16::new(&img_path)?;
+    let err = fs.read_directory(&"/test_dir_3".into()).unwrap_err();
+    match err.downcast_ref::<Fat16Error>() {
+        Some(Fat16Error::DirectoryChainBroken { path, .. }) => assert_eq!(path, "/test_dir_3"),
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    // 配下のファイルを辿る場合も同様
+    let err = fs.read_file(&"/test_dir_3/long_1.txt".into()).unwrap_err();
+    match err.downcast_ref::<Fat16Error>() {
+        Some(Fat16Error::DirectoryChainBroken { path, .. }) => assert_eq!(path, "/test_dir_3"),
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    // 他のディレクトリは読める
+    assert_eq!(fs.read_directory(&"/test_dir_1".into())?.len(), 4 + 2);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn case_insensitive_lookup() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, Path};
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // 大文字で指定しても小文字のファイルが見つかる
+    let path = Path::from("/1.TXT");
+    assert_eq!(fs.read_file(&path)?, b"No.1\n");
+
+    // 入力されたパスとディスク上の名前をそれぞれ取得できる
+    assert_eq!(path.original(), "/1.TXT");
+    assert_eq!(fs.canonicalize(&path)?, "/1.txt");
+    assert_eq!(fs.canonicalize(&"/TEST_DIR_1/Test_Dir_1_1/2.TXT".into())?, "/test_dir_1/test_dir_1_1/2.txt");
+
+    Ok(())
+}
+
+#[test]
+fn case_sensitive_lookup() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16Builder;
+
+    let mut fs = Fat16Builder::new().build()?;
+    fs.create_dir(&"/Docs".into())?;
+    fs.create_file(&"/Docs/ReadMe.txt".into())?;
+    fs.write_file(&"/Docs/ReadMe.txt".into(), b"hello")?;
+
+    // 通常の検索は大文字・小文字を区別しない
+    assert_eq!(fs.read_file(&"/docs/readme.txt".into())?, b"hello");
+
+    // find_exact は入力されたままの名前で比較する
+    assert_eq!(fs.find_exact(&"/Docs/ReadMe.txt".into())?.name, "ReadMe.txt");
+    assert!(fs.find_exact(&"/Docs/readme.txt".into()).is_err());
+    assert!(fs.find_exact(&"/docs/ReadMe.txt".into()).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn allocation_bitmap() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+    use fatfs::{FileSystem as FatFs, FsOptions};
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(&img_path)?;
+    let bitmap = fs.allocation_bitmap();
+
+    // fatfs クレートの統計情報と使用中クラスタ数が一致する
+    let img_file = OpenOptions::new().read(true).write(true).open(&img_path)?;
+    let stats = FatFs::new(img_file, FsOptions::new())?.stats()?;
+    let allocated = bitmap.iter().filter(|b| **b).count();
+    assert_eq!(allocated as u32, stats.total_clusters() - stats.free_clusters());
+    assert_eq!(bitmap.len() as u32, fs.cluster_count());
+    assert_eq!(fs.cluster_count(), stats.total_clusters());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn fat_entry_count() -> Result<(), Box<dyn StdError>> {
+    use std::io::Cursor;
+    use fat16_test::{Fat16, Fat16Builder};
+    use fatfs::{FileSystem as FatFs, FsOptions};
+
+    // large_sectors を使うイメージ (128MB) と total_sectors を使うイメージ (16MB)
+    let img_path = init_fat16()?;
+    let large = fs::read(img_path)?;
+    let small = Fat16Builder::new().total_sectors(32768).build_bytes()?;
+    for bytes in [large, small] {
+        // FAT エントリはデータクラスタ数 + 予約エントリ 2 つだけ読む
+        let fs = Fat16::from_bytes(bytes.clone())?;
+        let stats = FatFs::new(Cursor::new(bytes), FsOptions::new())?.stats()?;
+        assert_eq!(fs.alloc_table.entry_count() as u32, stats.total_clusters() + 2);
+        assert!(fs.alloc_table.entry(stats.total_clusters() as u16 + 1).is_some());
+        assert!(fs.alloc_table.entry(stats.total_clusters() as u16 + 2).is_none());
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn mbr_partition() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // FAT16 イメージを 1MB オフセットのパーティションとして MBR で包む
+    let img_path = init_fat16()?;
+    let volume = fs::read(&img_path)?;
+    let start_lba = 2048u32;
+    let mut disk = vec![0u8; start_lba as usize * 512];
+    disk[0x1BE + 4] = 0x06;
+    disk[0x1BE + 8..0x1BE + 12].copy_from_slice(&start_lba.to_le_bytes());
+    disk[0x1BE + 12..0x1BE + 16].copy_from_slice(&((volume.len() / 512) as u32).to_le_bytes());
+    disk[510..512].copy_from_slice(&[0x55, 0xAA]);
+    disk.extend(&volume);
+
+    let disk_path = format!("{}/target/tmp/fat16_mbr.img", env::var("CARGO_MANIFEST_DIR")?);
+    fs::write(&disk_path, &disk)?;
+
+    // パーティション 0 をマウントして読める
+    let fs = Fat16::from_partition(&disk_path, 0)?;
+    assert_eq!(fs.ebpb.boot_partition_signature, [0x55, 0xAA]);
+    assert_eq!(fs.read_file(&"/test_dir_1/test_dir_1_1/2.txt".into())?, b"No.1-1-2\n");
+
+    // FAT16 でないパーティションはエラー
+    assert!(Fat16::from_partition(&disk_path, 1).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_file_tail() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // 末尾の n バイトを読む
+    assert_eq!(fs.read_file_tail(&"/1.txt".into(), 4)?, b"o.1\n");
+    assert_eq!(fs.read_file_tail(&"/1.txt".into(), 100)?, b"No.1\n");
+    assert_eq!(fs.read_file_tail(&"/1.txt".into(), 0)?, b"");
+
+    // 複数クラスタにまたがるファイル
+    let tail = fs.read_file_tail(&"/test_dir_3/long_2.txt".into(), 2500)?;
+    assert_eq!(tail, vec![0x62; 2500]);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn from_bytes() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // ファイルを経由せずバイト列から読み込む
+    let img_path = init_fat16()?;
+    let fs = Fat16::from_bytes(fs::read(img_path)?)?;
+    assert_eq!(fs.bpb.x86_jmp, [0xEB, 0x3C, 0x90]);
+    assert_eq!(fs.ebpb.boot_partition_signature, [0x55, 0xAA]);
+    assert_eq!(fs.read_file(&"/1.txt".into())?, b"No.1\n");
+    assert_eq!(fs.read_file(&"/test_dir_1/test_dir_1_1/2.txt".into())?, b"No.1-1-2\n");
+    assert_eq!(fs.read_file(&"/test_dir_3/long_1.txt".into())?, vec![0x61; 3000]);
+
+    Ok(())
+}
+
+#[test]
+fn short_boot_sector() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, Fat16BPB, Fat16EBPB, FatVolume};
+
+    // 512 バイトに満たない入力はパニックせずエラーになる
+    let bytes = vec![0u8; 100];
+    let err = Fat16BPB::parse(&bytes).unwrap_err();
+    assert_eq!(err.to_string(), "image too small: need at least 512 bytes, got 100");
+    let err = Fat16EBPB::parse(&bytes).unwrap_err();
+    assert_eq!(err.to_string(), "EBPB too small: need at least 476 bytes, got 100");
+    assert!(Fat16::from_bytes(bytes.clone()).is_err());
+    assert!(FatVolume::from_bytes(bytes).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn from_reader() -> Result<(), Box<dyn StdError>> {
+    use std::io::Cursor;
+    use fat16_test::Fat16;
+
+    // データ領域は必要になった時に読み込む
+    let img_path = init_fat16()?;
+    let fs = Fat16::from_reader(Cursor::new(fs::read(&img_path)?))?;
+    assert!(fs.clusters.is_empty());
+    assert_eq!(fs.bpb.x86_jmp, [0xEB, 0x3C, 0x90]);
+    assert_eq!(fs.ebpb.boot_partition_signature, [0x55, 0xAA]);
+    assert_eq!(fs.read_file(&"/1.txt".into())?, b"No.1\n");
+    assert_eq!(fs.read_file(&"/test_dir_1/test_dir_1_1/2.txt".into())?, b"No.1-1-2\n");
+    assert_eq!(fs.read_file(&"/test_dir_3/long_1.txt".into())?, vec![0x61; 3000]);
+
+    // ファイルを直接渡すこともできる
+    let fs = Fat16::from_reader(File::open(&img_path)?)?;
+    assert_eq!(fs.read_directory(&"/test_dir_1".into())?.len(), 4 + 2);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn verify_all_chains() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{ChainError, Fat16, Fat16Error};
+
+    // 正常なイメージ
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    fs.verify_all_chains()?;
+
+    // long_1.txt (2 クラスタ) の末尾を先頭に繋いで循環させる
+    let entries = fs.read_directory(&"/test_dir_3".into())?;
+    let entry = entries.iter().find(|e| e.name == "long_1.txt").unwrap();
+    let first = entry.first_cluster as u16;
+    set_fat_entry(&mut bytes, &fs, first + 1, first);
+
+    let fs = Fat16::from_bytes(bytes)?;
+    let err = fs.verify_all_chains().unwrap_err();
+    match err.downcast_ref::<Fat16Error>() {
+        Some(Fat16Error::BrokenChain { path, error }) => {
+            assert_eq!(path, "/test_dir_3/long_1.txt");
+            assert_eq!(*error, ChainError::Cycle(first));
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn verify_file_size() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, Fat16Error};
+
+    // 正常なイメージ (空でないファイルは 1 クラスタ)
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    fs.verify_file_size(&"/1.txt".into())?;
+    fs.verify_file_size(&"/test_dir_3/long_1.txt".into())?;
+
+    // 1.txt の file_size を 3 クラスタ分に書き換える
+    let file_size = fs.bytes_per_cluster() as u32 * 2 + 1;
+    patch_root_entry(&mut bytes, &fs, b"1       TXT", |entry| entry[28..32].copy_from_slice(&file_size.to_le_bytes()));
+
+    let fs = Fat16::from_bytes(bytes)?;
+    let err = fs.verify_file_size(&"/1.txt".into()).unwrap_err();
+    match err.downcast_ref::<Fat16Error>() {
+        Some(Fat16Error::FileSizeMismatch { path, expected_clusters, chain_clusters, .. }) => {
+            assert_eq!(path, "/1.txt");
+            assert_eq!(*expected_clusters, 3);
+            assert_eq!(*chain_clusters, 1);
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn file_checksum() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // zlib の crc32 と同じ値
+    assert_eq!(fs.file_checksum(&"/1.txt".into())?, 0x56FF7326);
+    assert_eq!(fs.file_checksum(&"/test_dir_3/long_1.txt".into())?, 0x5EDC3CC8);
+
+    // クラスタ毎に file_size までの内容が渡される
+    let mut chunks = vec![];
+    fs.for_each_chunk(&"/test_dir_3/long_1.txt".into(), |chunk| chunks.push(chunk.len()))?;
+    assert_eq!(chunks, [2048, 952]);
+
+    // チェーンが file_size より短い場合は切り詰めた内容のチェックサムを返さない
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let entry = fs.read_directory(&"/test_dir_3".into())?.into_iter().find(|e| e.name == "long_1.txt").unwrap();
+    set_fat_entry(&mut bytes, &fs, entry.first_cluster as u16, 0xFFFF);
+    let fs = Fat16::from_bytes(bytes)?;
+    let err = fs.file_checksum(&"/test_dir_3/long_1.txt".into()).unwrap_err();
+    assert_eq!(err.to_string(), "Cluster chain of '/test_dir_3/long_1.txt' is shorter than its file size (3000 bytes)");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn huge_file_size() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // 1.txt の file_size をデータ領域より大きい値に書き換える
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    patch_root_entry(&mut bytes, &fs, b"1       TXT", |entry| entry[28..32].copy_from_slice(&u32::MAX.to_le_bytes()));
+
+    let fs = Fat16::from_bytes(bytes)?;
+    let err = fs.read_file(&"/1.txt".into()).unwrap_err();
+    assert!(err.to_string().contains("exceeds the data region"), "{}", err);
+    assert!(fs.read_file_tail(&"/1.txt".into(), 5).is_err());
+    assert!(fs.read_file_range(&"/1.txt".into(), 0, 5).is_err());
+    assert!(fs.file_checksum(&"/1.txt".into()).is_err());
+
+    // 他のファイルは読める
+    assert_eq!(fs.read_file(&"/2.txt".into())?, b"No.2\n");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn find_by_attribute() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // fatfs はアーカイブ属性を立てないので、いくつかのファイルに直接立てる
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    patch_root_entry(&mut bytes, &fs, b"1       TXT", |entry| entry[11] |= 0x20);
+    patch_root_entry(&mut bytes, &fs, b"3       TXT", |entry| entry[11] |= 0x20);
+    let fs = Fat16::from_bytes(bytes)?;
+
+    // アーカイブ属性が立っているファイルのみ
+    let found = fs.find_by_attribute(&"/".into(), 0x20, 0x20)?;
+    let paths = found.iter().map(|(path, _)| path.original()).collect::<Vec<_>>();
+    assert_eq!(paths, ["/1.txt", "/3.txt"]);
+
+    // サブディレクトリ以下のディレクトリのみ
+    let found = fs.find_by_attribute(&"/test_dir_1".into(), 0x10, 0x10)?;
+    let paths = found.iter().map(|(path, _)| path.original()).collect::<Vec<_>>();
+    assert_eq!(paths, ["/test_dir_1/test_dir_1_1"]);
+
+    // ディレクトリ・ボリュームラベル以外 (= ファイル) の数
+    let found = fs.find_by_attribute(&"/".into(), 0x18, 0x00)?;
+    assert_eq!(found.len(), 15);
+
+    Ok(())
+}
+
+// ルートディレクトリ領域から 8.3 形式の名前が一致するエントリを探して書き換える
+fn patch_root_entry(bytes: &mut [u8], fs: &fat16_test::Fat16, sfn: &[u8; 11], f: impl FnOnce(&mut [u8])) {
+    let bytes_per_sector = fs.bpb.bytes_per_sector as usize;
+    let root_offset = (fs.bpb.reserved_sector_count as usize + fs.bpb.num_fats as usize * fs.bpb.sectors_per_fat as usize) * bytes_per_sector;
+    let root_dir = &mut bytes[root_offset..root_offset + fs.bpb.root_entry_count as usize * 32];
+    let entry = root_dir
+        .chunks_mut(32)
+        .find(|entry| &entry[0..11] == sfn && entry[11] != 0x0f)
+        .unwrap();
+    f(entry);
+}
+
+#[serial]
+#[test]
+fn reserved_start_cluster() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // 1.txt とディレクトリ test_dir_1 の先頭クラスタを予約済みのクラスタ 1 にする
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    patch_root_entry(&mut bytes, &fs, b"1       TXT", |entry| entry[26..28].copy_from_slice(&1u16.to_le_bytes()));
+    patch_root_entry(&mut bytes, &fs, b"TEST_D~1   ", |entry| entry[26..28].copy_from_slice(&1u16.to_le_bytes()));
+    let fs = Fat16::from_bytes(bytes)?;
+
+    // FAT の予約エントリを辿らずにエラーになる
+    let err = fs.read_file(&"/1.txt".into()).unwrap_err();
+    assert_eq!(err.to_string(), "invalid start cluster 1");
+    let entry = fs.root_dir.iter().find(|e| e.name == "1.txt").unwrap();
+    assert_eq!(fs.read_entry(entry).unwrap_err().to_string(), "invalid start cluster 1");
+    let err = fs.read_directory(&"/test_dir_1".into()).unwrap_err();
+    assert_eq!(err.to_string(), "invalid start cluster 1");
+    let err = fs.read_file(&"/test_dir_1/1.txt".into()).unwrap_err();
+    assert_eq!(err.to_string(), "invalid start cluster 1");
+
+    // 他のファイルは読める
+    assert_eq!(fs.read_file(&"/2.txt".into())?, b"No.2\n");
+
+    // サイズのあるファイルの先頭クラスタが 0 の場合も空のファイルとして読まない
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    patch_root_entry(&mut bytes, &fs, b"3       TXT", |entry| entry[26..28].copy_from_slice(&0u16.to_le_bytes()));
+    let mut fs = Fat16::from_bytes(bytes)?;
+    let err = fs.read_file(&"/3.txt".into()).unwrap_err();
+    assert_eq!(err.to_string(), "invalid start cluster 0 (file_size = 5)");
+    let entry = fs.root_dir.iter().find(|e| e.name == "3.txt").unwrap();
+    assert_eq!(fs.read_entry(entry).unwrap_err().to_string(), "invalid start cluster 0 (file_size = 5)");
+
+    // 空のファイルは先頭クラスタ 0 のまま読める
+    fs.create_file(&"/empty.txt".into())?;
+    assert!(fs.read_file(&"/empty.txt".into())?.is_empty());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn stop_at_terminator() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, Fat16DirEntry};
+
+    // 終端 (0x00) の 1 つ後ろのスロットに 1.txt の SFN を AFTER.TXT として複製する
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let root_offset = fs.data_region_offset() - fs.bpb.root_entry_count as usize * 32;
+    let root_dir = &mut bytes[root_offset..root_offset + fs.bpb.root_entry_count as usize * 32];
+    let terminator = root_dir.chunks(32).position(|record| record[0] == 0x00).unwrap();
+    let mut record = root_dir.chunks(32).find(|record| &record[0..11] == b"1       TXT").unwrap().to_vec();
+    record[0..11].copy_from_slice(b"AFTER   TXT");
+    root_dir[(terminator + 1) * 32..(terminator + 2) * 32].copy_from_slice(&record);
+
+    // 終端以降のエントリは返さない
+    let (entries, rest) = Fat16DirEntry::parses_until_terminator(root_dir, fs.bpb.root_entry_count)?;
+    assert!(rest.is_empty());
+    assert!(entries.iter().all(|e| e.name != "AFTER.TXT"));
+    let (entries, _) = Fat16DirEntry::parses(root_dir, fs.bpb.root_entry_count)?;
+    assert!(entries.iter().any(|e| e.name == "AFTER.TXT"));
+
+    let mut fs = Fat16::from_bytes(bytes.clone())?;
+    assert!(fs.read_file(&"/after.txt".into()).is_err());
+    assert!(fs.remove_file(&"/after.txt".into()).is_err());
+
+    // 書き込みは終端のスロットから使い、その後ろのスロットは終端として 0x00 にする
+    fs.create_file(&"/new file.txt".into())?;
+    assert!(fs.exists(&"/new file.txt".into()));
+    let raw = fs.read_dir_raw(&"/".into())?;
+    assert_eq!(raw[terminator * 32 + 11], 0x0f);
+    assert_ne!(raw[(terminator + 1) * 32], 0x00);
+    assert_eq!(raw[(terminator + 2) * 32], 0x00);
+    assert!(fs.read_directory(&"/".into())?.iter().all(|e| e.name != "AFTER.TXT"));
+
+    // 終端を維持していないイメージ向けに無効化できる
+    let mut fs = Fat16::from_bytes(bytes)?;
+    fs.set_stop_at_terminator(false)?;
+    assert_eq!(fs.read_file(&"/after.txt".into())?, b"No.1\n");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn update_entry() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+    use fat16_test::fs as fat_fs;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::from_bytes(fs::read(img_path)?)?;
+
+    // ルートディレクトリとサブディレクトリのエントリに読み込み専用属性を付ける
+    fs.update_entry(&"/1.txt".into(), |entry| entry.attribute |= 0x01)?;
+    fs.update_entry(&"/test_dir_3/long_2.txt".into(), |entry| entry.attribute |= 0x01)?;
+    assert!(fat_fs::metadata(&fs, "/1.txt")?.readonly());
+    assert!(fat_fs::metadata(&fs, "/test_dir_3/long_2.txt")?.readonly());
+    assert!(!fat_fs::metadata(&fs, "/test_dir_3/long_1.txt")?.readonly());
+
+    // 属性以外のフィールドや内容は変わらない
+    let before = fs.read_directory(&"/".into())?.into_iter().find(|e| e.name == "2.txt").unwrap();
+    fs.update_entry(&"/2.txt".into(), |_| {})?;
+    let after = fs.read_directory(&"/".into())?.into_iter().find(|e| e.name == "2.txt").unwrap();
+    assert_eq!(format!("{:?}", before), format!("{:?}", after));
+    assert_eq!(fs.read_file(&"/test_dir_3/long_2.txt".into())?, vec![0x62; 3000]);
+
+    // 読み込み専用属性を外す
+    fs.update_entry(&"/1.txt".into(), |entry| entry.attribute &= !0x01)?;
+    assert!(!fat_fs::metadata(&fs, "/1.txt")?.readonly());
+
+    // 名前は変更できない
+    assert!(fs.update_entry(&"/1.txt".into(), |entry| entry.name = "9.txt".to_string()).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_directory_filtered() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // 2.txt を隠しファイル、3.txt をシステムファイルにする
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    patch_root_entry(&mut bytes, &fs, b"2       TXT", |entry| entry[11] |= 0x02);
+    patch_root_entry(&mut bytes, &fs, b"3       TXT", |entry| entry[11] |= 0x04);
+    let fs = Fat16::from_bytes(bytes)?;
+
+    let names = |entries: Vec<fat16_test::Fat16DirEntry>| entries.into_iter().map(|e| e.name).collect::<Vec<_>>();
+    let visible = names(fs.read_directory_filtered(&"/".into(), false)?);
+    assert!(visible.contains(&"1.txt".to_string()));
+    assert!(!visible.contains(&"2.txt".to_string()));
+    assert!(!visible.contains(&"3.txt".to_string()));
+
+    // show_hidden なら read_directory と同じ
+    assert_eq!(names(fs.read_directory_filtered(&"/".into(), true)?), names(fs.read_directory(&"/".into())?));
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_directory_sorted() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, SortKey};
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::from_bytes(fs::read(img_path)?)?;
+
+    // 末尾に追加した Long_0.txt が名前順では先頭になる
+    fs.create_file(&"/test_dir_3/Long_0.txt".into())?;
+    fs.write_file(&"/test_dir_3/Long_0.txt".into(), &[0x64; 2000])?;
+    fs.write_file(&"/test_dir_3/long_1.txt".into(), &[0x61; 4000])?;
+    fs.write_file(&"/test_dir_3/long_2.txt".into(), &[0x62; 1000])?;
+
+    let names = |sort: SortKey| -> Result<Vec<String>, Box<dyn StdError>> {
+        Ok(fs.read_directory_sorted(&"/test_dir_3".into(), sort)?
+            .into_iter()
+            .filter(|e| e.name.to_lowercase().starts_with("long_"))
+            .map(|e| e.name)
+            .collect())
+    };
+    assert_eq!(names(SortKey::None)?, vec!["long_1.txt", "long_2.txt", "long_3.txt", "Long_0.txt"]);
+    assert_eq!(names(SortKey::Name)?, vec!["Long_0.txt", "long_1.txt", "long_2.txt", "long_3.txt"]);
+    assert_eq!(names(SortKey::Size)?, vec!["long_2.txt", "Long_0.txt", "long_3.txt", "long_1.txt"]);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_dir_raw() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::from_bytes(fs::read(img_path)?)?;
+
+    // サブディレクトリはクラスタ単位で読み込まれる
+    let raw = fs.read_dir_raw(&"/test_dir_3".into())?;
+    assert!(!raw.is_empty());
+    assert_eq!(raw.len() % fs.bytes_per_cluster(), 0);
+
+    // . と .. の SFN エントリを含む
+    assert!(raw.chunks(32).any(|record| &record[0..11] == b".          "));
+    assert!(raw.chunks(32).any(|record| &record[0..11] == b"..         "));
+
+    // ルートディレクトリは固定サイズの領域そのまま
+    assert_eq!(fs.read_dir_raw(&"/".into())?.len(), fs.bpb.root_entry_count as usize * 32);
+    assert!(fs.read_dir_raw(&"/1.txt".into()).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn iterate_root_entries() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::from_bytes(fs::read(img_path)?)?;
+
+    let mut names = vec![];
+    for entry in &fs {
+        names.push(entry.name.clone());
+    }
+    assert_eq!(names.len(), fs.root_dir.len());
+    assert_eq!(names, fs.root_dir.iter().map(|e| e.name.clone()).collect::<Vec<_>>());
+    assert!((&fs).into_iter().any(|entry| entry.name == "1.txt"));
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn volume_summary() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, FatType};
+    use fatfs::{FileSystem as FatFs, FsOptions};
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::from_bytes(fs::read(&img_path)?)?;
+    let summary = fs.summary();
+    assert_eq!(summary.bytes_per_sector, 512);
+    assert_eq!(summary.bytes_per_cluster, fs.bytes_per_cluster());
+    assert_eq!(summary.total_clusters, fs.cluster_count());
+    assert_eq!(summary.num_fats, 2);
+    assert_eq!(summary.root_entry_count, 512);
+    assert_eq!(summary.volume_label, "FAT16IMG");
+    assert_eq!(summary.volume_id, "CAFE-BABE");
+    assert_eq!(summary.fat_type, FatType::Fat16);
+
+    // クラスタ数と空きクラスタ数は fatfs クレートの値と一致する
+    let stats = FatFs::new(File::open(&img_path)?, FsOptions::new())?.stats()?;
+    assert_eq!(summary.total_clusters, stats.total_clusters());
+    assert_eq!(summary.free_clusters, stats.free_clusters());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn image_geometry() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let geometry = fs.image_geometry();
+
+    // メタデータ + データ領域 = BPB 上のボリュームサイズ
+    assert_eq!(geometry.total_bytes, 128 * 1024 * 1024);
+    assert_eq!(geometry.metadata_bytes + geometry.data_bytes, geometry.total_bytes);
+    assert_eq!(geometry.metadata_bytes, (1 + 2 * 256 + 32) * 512);
+    assert_eq!(geometry.cluster_count, fs.cluster_count());
+    assert_eq!(geometry.image_bytes, geometry.total_bytes);
+    assert_eq!(geometry.slack_bytes, 0);
+
+    // ボリュームより大きいイメージは末尾の余りとして報告される
+    bytes.extend(vec![0; 4096]);
+    let fs = Fat16::from_bytes(bytes)?;
+    assert_eq!(fs.image_geometry().slack_bytes, 4096);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn geometry_accessors() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // 128MB, 512 バイト/セクタ, 4 セクタ/クラスタ, 予約 1 セクタ, FAT 256 セクタ x 2, ルート 512 エントリ
+    assert_eq!(fs.bytes_per_cluster(), 2048);
+    assert_eq!(fs.root_dir_sectors(), 32);
+    assert_eq!(fs.data_region_offset(), (1 + 2 * 256 + 32) * 512);
+    assert_eq!(fs.cluster_count(), (128 * 1024 * 1024 / 512 - (1 + 2 * 256 + 32)) / 4);
+
+    // クラスタ 2 はデータ領域の先頭
+    assert_eq!(fs.locate(&"/1.txt".into())?, [(2, fs.data_region_offset())]);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn cluster_bytes() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // クラスタ 2 は /1.txt の内容
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(&img_path)?;
+    let cluster = fs.cluster_bytes(2)?;
+    assert_eq!(cluster.len(), fs.bytes_per_cluster());
+    assert_eq!(&cluster[..5], b"No.1\n");
+    assert!(fs.cluster_bytes(0).is_err());
+    assert!(fs.cluster_bytes(u16::MAX).is_err());
+
+    // その場で書き換える
+    fs.cluster_bytes_mut(2)?[..5].copy_from_slice(b"No.9\n");
+    assert_eq!(fs.read_file(&"/1.txt".into())?, b"No.9\n");
+    assert!(fs.cluster_bytes_mut(1).is_err());
+
+    // ブロックデバイスから開いた場合も読める (書き換えはできない)
+    let mut fs = Fat16::open(&img_path)?;
+    assert_eq!(fs.cluster_bytes(2)?.len(), fs.bytes_per_cluster());
+    assert!(fs.cluster_bytes_mut(2).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn verify_fat_mirror() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // 2 つの FAT は一致している
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    assert_eq!(fs.alloc_table.verify_mirror(), None);
+
+    // 2 つ目の FAT のクラスタ 10 のエントリを 1 バイト壊す
+    let bytes_per_sector = fs.bpb.bytes_per_sector as usize;
+    let second_fat = (fs.bpb.reserved_sector_count as usize + fs.bpb.sectors_per_fat as usize) * bytes_per_sector;
+    bytes[second_fat + 10 * 2] ^= 0xFF;
+
+    let fs = Fat16::from_bytes(bytes)?;
+    assert_eq!(fs.alloc_table.verify_mirror(), Some(10));
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn oem_quirks() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, OemQuirks};
+
+    // fatfs クレートは "MSWIN4.1" でフォーマットする
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    assert_eq!(&fs.bpb.oem_name, b"MSWIN4.1");
+    assert_eq!(fs.bpb.oem_quirks(), OemQuirks { known: true, writes_lfn: true, nt_case_flags: false });
+
+    // 未知の OEM 名
+    bytes[3..11].copy_from_slice(b"UNKNOWN ");
+    let fs = Fat16::from_bytes(bytes)?;
+    assert!(!fs.bpb.oem_quirks().known);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn oem_name_and_boot_code() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    assert_eq!(fs.oem_name(), "MSWIN4.1");
+    assert_eq!(fs.boot_code(), &bytes[62..510]);
+
+    // 末尾の空白は取り除く
+    bytes[3..11].copy_from_slice(b"mkdosfs ");
+    let fs = Fat16::from_bytes(bytes)?;
+    assert_eq!(fs.oem_name(), "mkdosfs");
+
+    Ok(())
+}
+
+#[test]
+fn sfn_cp437() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16DirEntry;
+
+    // LFN を持たない SFN エントリ "CAF\x82.TXT" (0x82 は CP437 で 'é')
+    let mut record = [0u8; 32];
+    record[0..11].copy_from_slice(b"CAF\x82    TXT");
+    record[11] = 0x20;
+
+    let (entry, _) = Fat16DirEntry::parse_entry(&record)?;
+    assert_eq!(entry.unwrap().name, "CAFé.TXT");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_file_contiguous() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // 1 クラスタ・連続した 2 クラスタのファイルは借用で返す
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(&img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    assert_eq!(fs.read_file_contiguous(&"/1.txt".into())?, Some(&b"No.1\n"[..]));
+    let long_1 = fs.read_file_contiguous(&"/test_dir_3/long_1.txt".into())?.unwrap();
+    assert_eq!(long_1, &[0x61; 3000][..]);
+
+    // 連続していないチェーンは None (コピーする読み込みを使う)
+    let first = fs.read_directory(&"/test_dir_3".into())?.iter().find(|e| e.name == "long_1.txt").unwrap().first_cluster as u16;
+    let free = fs.alloc_table.next_free_hint().unwrap();
+    set_fat_entry(&mut bytes, &fs, first, free);
+    set_fat_entry(&mut bytes, &fs, free, 0xFFFF);
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    assert_eq!(fs.read_file_contiguous(&"/test_dir_3/long_1.txt".into())?, None);
+
+    // チェーンが file_size より短い場合は None ではなくエラー
+    set_fat_entry(&mut bytes, &fs, first, 0xFFFF);
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let err = fs.read_file_contiguous(&"/test_dir_3/long_1.txt".into()).unwrap_err();
+    assert_eq!(err.to_string(), "Cluster chain of '/test_dir_3/long_1.txt' is shorter than its file size (3000 bytes)");
+
+    // データ領域が切り詰められている場合もエラー
+    let mut bytes = fs::read(&img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let (cluster, offset) = fs.locate(&"/test_dir_3/long_1.txt".into())?[0];
+    assert_eq!(cluster, first);
+    bytes.truncate(offset + 100);
+    let fs = Fat16::from_bytes(bytes)?;
+    assert!(fs.read_file_contiguous(&"/test_dir_3/long_1.txt".into()).is_err());
+
+    // ブロックデバイスから開いた場合も None
+    let fs = Fat16::open(&img_path)?;
+    assert_eq!(fs.read_file_contiguous(&"/1.txt".into())?, None);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_file_verify_order() -> Result<(), Box<dyn StdError>> {
+    use std::io::{Seek, SeekFrom};
+    use fatfs::{FileSystem as FatFs, FsOptions};
+    use fat16_test::{ChainError, Fat16, Fat16Error};
+
+    // 間に別のファイルを挟んで追記し、断片化したファイルを作る
+    let img_path = init_fat16()?;
+    {
+        let img_file = OpenOptions::new().read(true).write(true).open(&img_path)?;
+        let fatfs = FatFs::new(img_file, FsOptions::new())?;
+        let root_dir = fatfs.root_dir();
+        root_dir.create_file("frag.txt")?.write_all(&[0x41; 1000])?;
+        root_dir.create_file("gap.txt")?.write_all(&[0x42; 3000])?;
+        let mut file = root_dir.open_file("frag.txt")?;
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(&[0x43; 3000])?;
+    }
+
+    let mut bytes = fs::read(&img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let mut expected = vec![0x41; 1000];
+    expected.extend([0x43; 3000]);
+    assert_eq!(fs.read_file_verify_order(&"/frag.txt".into())?, expected);
+
+    // 2 つ目のクラスタから先頭へ戻るようにして循環させる
+    let entry = fs.root_dir.iter().find(|e| e.name == "frag.txt").unwrap();
+    let first = entry.first_cluster as u16;
+    let chain = fs.alloc_table.get_cluster_chain(first)?;
+    assert!(chain.windows(2).any(|w| w[1] != w[0] + 1));
+    set_fat_entry(&mut bytes, &fs, chain[1], first);
+
+    let fs = Fat16::from_bytes(bytes)?;
+    let err = fs.read_file_verify_order(&"/frag.txt".into()).unwrap_err();
+    match err.downcast_ref::<Fat16Error>() {
+        Some(Fat16Error::BrokenChain { error, .. }) => assert_eq!(*error, ChainError::Cycle(first)),
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn sfn_kanji_lead_byte() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16DirEntry;
+
+    // 実際の先頭バイト 0xE5 は 0x05 として格納される
+    let mut record = [0u8; 32];
+    record[0..11].copy_from_slice(b"\x05ABC    TXT");
+    record[11] = 0x20;
+
+    let (entry, _) = Fat16DirEntry::parse_entry(&record)?;
+    assert_eq!(entry.unwrap().name, "σABC.TXT");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn create_dir_long_name() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+
+    // LFN が必要な名前のディレクトリを作成する
+    fs.create_dir(&"/My Long Directory Name".into())?;
+    fs.create_dir(&"/My Long Directory Name 2".into())?;
+    fs.create_dir(&"/test_dir_1/Nested Long Directory".into())?;
+
+    // 長い名前で一覧・参照できる
+    let names = fs.root_dir.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+    assert!(names.contains(&"My Long Directory Name"));
+    assert!(names.contains(&"My Long Directory Name 2"));
+    assert!(fs.read_directory(&"/my long directory name".into())?.iter().all(|e| e.is_dot()));
+    let nested = fs.read_directory(&"/test_dir_1".into())?;
+    assert!(nested.iter().any(|e| e.name == "Nested Long Directory" && e.is_dir()));
+
+    // 同じ名前は作成できない
+    assert!(fs.create_dir(&"/MY LONG DIRECTORY NAME".into()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn lfn_checksum_mismatch() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16DirEntry;
+
+    // "long.text" の LFN エントリ + "LONG    TEX" の SFN エントリ
+    let build = |checksum: Option<u8>| {
+        let mut records = lfn_records("long.text", b"LONG    TEX");
+        if let Some(checksum) = checksum {
+            records[13] = checksum;
+        }
+        records
+    };
+    let checksum = build(None)[13];
+
+    // チェックサムが一致すれば LFN の名前
+    let (entry, _) = Fat16DirEntry::parse_entry(&build(None))?;
+    assert_eq!(entry.unwrap().name, "long.text");
+
+    // 一致しなければ SFN の名前にフォールバックする
+    let records = build(Some(checksum.wrapping_add(1)));
+    let (entry, rest) = Fat16DirEntry::parse_entry(&records)?;
+    assert_eq!(entry.unwrap().name, "LONG.TEX");
+    assert!(rest.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn orphaned_lfn() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16DirEntry;
+
+    // LFN の後ろの SFN が削除済み (0xE5) または未使用 (0x00) で、その後に有効なエントリが続く
+    for marker in [0xE5, 0x00] {
+        let mut records = lfn_records("orphaned long name.txt", b"ORPHAN~1TXT");
+        let sfn = records.len() - 32;
+        records[sfn] = marker;
+        records.extend(&lfn_records("b.txt", b"B       TXT")[32..]);
+
+        // LFN は捨てられ、LFN の後ろ (SFN の位置) まで読み進める
+        let (entry, rest) = Fat16DirEntry::parse_entry(&records)?;
+        assert!(entry.is_none());
+        assert_eq!(rest.len(), records.len() - sfn);
+
+        // 後続のエントリは通常どおり読める
+        let (entries, _) = Fat16DirEntry::parses(&records, (records.len() / 32) as u16)?;
+        let names = entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["B.TXT"]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn lfn_surrogate_pair() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16DirEntry;
+
+    // 絵文字 (サロゲートペア) の上位が 1 つ目の断片の末尾、下位が 2 つ目の断片の先頭に入る
+    let long_name = "abcdefghijkl\u{1F600}.txt";
+    assert_eq!(long_name.encode_utf16().position(|unit| (0xD800..0xDC00).contains(&unit)), Some(12));
+    let mut records = lfn_records(long_name, b"ABCDEF~1TXT");
+    let (entry, rest) = Fat16DirEntry::parse_entry(&records)?;
+    assert_eq!(entry.unwrap().name, long_name);
+    assert!(rest.is_empty());
+
+    // 対になっていないサロゲートは SFN の名前にフォールバックする
+    records[1..3].copy_from_slice(&(b'x' as u16).to_le_bytes());
+    let (entry, rest) = Fat16DirEntry::parse_entry(&records)?;
+    assert_eq!(entry.unwrap().name, "ABCDEF~1.TXT");
+    assert!(rest.is_empty());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn next_free_hint() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+    let table = &mut fs.alloc_table;
+
+    // 連続して確保すると、カーソルが前に進んでいく
+    let clusters = (0..100).map(|_| table.allocate_cluster().unwrap()).collect::<Vec<_>>();
+    assert!(clusters.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(table.next_free_hint(), Some(clusters[99] + 1));
+
+    // カーソルより前の空きは再走査されない
+    table.set_entry(clusters[0], 0x0000);
+    assert_eq!(table.allocate_cluster(), Some(clusters[99] + 1));
+
+    // free_chain でカーソルが先頭に戻る
+    table.free_chain(clusters[1]);
+    assert_eq!(table.next_free_hint(), Some(clusters[0]));
+    assert_eq!(table.allocate_cluster(), Some(clusters[0]));
+    assert_eq!(table.allocate_cluster(), Some(clusters[1]));
+
+    Ok(())
+}
+
+#[test]
+fn lfn_sequence_validation() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16DirEntry;
+
+    // 2 つの LFN エントリに分かれる名前
+    let long_name = "a long file name.txt";
+    let records = lfn_records(long_name, b"ALONGF~1TXT");
+    let (entry, _) = Fat16DirEntry::parse_entry(&records)?;
+    assert_eq!(entry.unwrap().name, long_name);
+
+    // 最後の断片を示すフラグ (0x40) がない
+    let mut missing_last = records.clone();
+    missing_last[0] &= !0x40;
+    let (entry, rest) = Fat16DirEntry::parse_entry(&missing_last)?;
+    assert_eq!(entry.unwrap().name, "ALONGF~1.TXT");
+    assert!(rest.is_empty());
+
+    // シーケンス番号の順序が入れ替わっている (0x41, 0x02)
+    let mut out_of_order = records.clone();
+    out_of_order[0] = 0x41;
+    out_of_order[32] = 0x02;
+    let (entry, _) = Fat16DirEntry::parse_entry(&out_of_order)?;
+    assert_eq!(entry.unwrap().name, "ALONGF~1.TXT");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn lfn_across_cluster_boundary() -> Result<(), Box<dyn StdError>> {
+    use fatfs::{FileSystem as FatFs, FsOptions};
+    use fat16_test::Fat16;
+
+    // . と .. で 4 スロット、ファイル 1 つにつき 2 スロット (LFN + SFN) 使われる
+    // 29 個のファイルの後に置いた長い名前 (LFN 2 つ + SFN) がクラスタ境界をまたぐ
+    let long_name = "boundary long name.txt";
+    let img_path = init_fat16()?;
+    {
+        let img_file = OpenOptions::new().read(true).write(true).open(&img_path)?;
+        let fatfs = FatFs::new(img_file, FsOptions::new())?;
+        let dir = fatfs.root_dir().create_dir("boundary")?;
+        for i in 0..29 {
+            dir.create_file(&format!("F{}.TXT", i))?;
+        }
+        dir.create_file(long_name)?.write_all(b"boundary\n")?;
+    }
+
+    // 1 つ目のクラスタの末尾が LFN エントリで終わっていることを確認
+    let fs = Fat16::new(&img_path)?;
+    let bytes_per_cluster = fs.bpb.bytes_per_sector as usize * fs.bpb.sectors_per_cluster as usize;
+    let dir = fs.root_dir.iter().find(|e| e.name == "boundary").unwrap();
+    let chain = fs.alloc_table.get_cluster_chain(dir.first_cluster as u16)?;
+    assert_eq!(chain.len(), 2);
+    let tail = (chain[0] as usize - 2) * bytes_per_cluster + bytes_per_cluster;
+    assert_eq!(fs.clusters[tail - 32 + 11], 0x0f);
+
+    // 長い名前が正しく復元される
+    let entries = fs.read_directory(&"/boundary".into())?;
+    assert!(entries.iter().any(|e| e.name == long_name));
+    assert_eq!(fs.read_file(&format!("/boundary/{}", long_name).as_str().into())?, b"boundary\n");
+
+    // クラスタを 1 つずつ読むブロックデバイス経由でも同じ
+    let fs = Fat16::open(&img_path)?;
+    let entries = fs.read_directory(&"/boundary".into())?;
+    assert!(entries.iter().any(|e| e.name == long_name));
+
+    Ok(())
+}
+
+#[test]
+fn deleted_entry_is_silent() -> Result<(), Box<dyn StdError>> {
+    use std::process::Command;
+
+    // 子プロセスとしてパースだけを行うテストを実行し、標準出力を調べる
+    let output = Command::new(env::current_exe()?)
+        .args(["deleted_entry_is_silent_child", "--exact", "--ignored", "--nocapture"])
+        .output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains("1 passed"), "{}", stdout);
+    let printed = stdout
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with("running ") && !line.starts_with("test "))
+        .collect::<Vec<_>>();
+    assert!(printed.is_empty(), "{:?}", printed);
+
+    Ok(())
+}
+
+#[test]
+#[ignore]
+fn deleted_entry_is_silent_child() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16DirEntry;
+
+    // 削除済み (0xE5) エントリと有効なエントリ
+    let mut records = [0u8; 64];
+    records[0..11].copy_from_slice(b"\xE5EMOVED TXT");
+    records[11] = 0x20;
+    records[32..43].copy_from_slice(b"ALIVE   TXT");
+    records[43] = 0x20;
+    let (entries, _) = Fat16DirEntry::parses(&records, 2)?;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "ALIVE.TXT");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn sfn_without_extension() -> Result<(), Box<dyn StdError>> {
+    use fatfs::{FileSystem as FatFs, FsOptions};
+    use fat16_test::{Fat16, Fat16DirEntry};
+
+    // 拡張子のない SFN エントリ
+    let mut record = [0u8; 32];
+    record[0..11].copy_from_slice(b"README     ");
+    record[11] = 0x20;
+    let (entry, _) = Fat16DirEntry::parse_entry(&record)?;
+    assert_eq!(entry.unwrap().name, "README");
+
+    // LFN を削除して SFN の名前だけが残るようにする
+    let img_path = init_fat16()?;
+    {
+        let img_file = OpenOptions::new().read(true).write(true).open(&img_path)?;
+        let fatfs = FatFs::new(img_file, FsOptions::new())?;
+        fatfs.root_dir().create_file("README")?.write_all(b"readme\n")?;
+    }
+    let mut bytes = fs::read(&img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let bytes_per_sector = fs.bpb.bytes_per_sector as usize;
+    let root_offset = (fs.bpb.reserved_sector_count as usize + fs.bpb.num_fats as usize * fs.bpb.sectors_per_fat as usize) * bytes_per_sector;
+    let root_dir = &mut bytes[root_offset..root_offset + fs.bpb.root_entry_count as usize * 32];
+    let idx = root_dir
+        .chunks(32)
+        .position(|entry| &entry[0..11] == b"README     " && entry[11] != 0x0f)
+        .unwrap();
+    assert_eq!(root_dir[(idx - 1) * 32 + 11], 0x0f);
+    root_dir[(idx - 1) * 32] = 0xE5;
+
+    // 末尾に '.' が付かず、入力したままの名前で参照できる
+    let fs = Fat16::from_bytes(bytes)?;
+    assert!(fs.root_dir.iter().any(|e| e.name == "README"));
+    assert_eq!(fs.read_file(&"/readme".into())?, b"readme\n");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn eoc_marker() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, FatType};
+
+    assert_eq!(FatType::Fat16.eoc_marker(), 0xFFF8);
+    assert_eq!(FatType::Fat16.bad_marker(), 0xFFF7);
+    assert_eq!(FatType::Fat12.eoc_marker(), 0x0FF8);
+    assert_eq!(FatType::Fat32.eoc_marker(), 0x0FFFFFF8);
+
+    // 0xFFF8 - 0xFFFF はどれもチェーンの終端として扱われる
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+    assert_eq!(fs.alloc_table.fat_type(), FatType::Fat16);
+    let first = fs.root_dir.iter().find(|e| e.name == "1.txt").unwrap().first_cluster as u16;
+    for marker in [0xFFF8, 0xFFFC, 0xFFFF] {
+        fs.alloc_table.set_entry(first, marker);
+        assert_eq!(fs.alloc_table.get_cluster_chain(first)?, vec![first]);
+        assert_eq!(fs.alloc_table.get_checked_cluster_chain(first)?, vec![first]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn fat16_builder() -> Result<(), Box<dyn StdError>> {
+    use std::io::{Cursor, Read};
+    use fatfs::{FileSystem as FatFs, FsOptions};
+    use fat16_test::{Fat16Builder, FatType};
+
+    // 16MB の空のイメージを作成して書き込み・読み込みする
+    let builder = Fat16Builder::new()
+        .bytes_per_sector(512)
+        .total_sectors(32768)
+        .volume_label(*b"BUILDER    ");
+    let mut fs = builder.build()?;
+    assert_eq!(fs.bpb.fat_type(), FatType::Fat16);
+    assert_eq!(fs.volume_label(), "BUILDER");
+    assert!(fs.root_dir.is_empty());
+    assert_eq!(fs.allocation_bitmap().iter().filter(|used| **used).count(), 0);
+
+    fs.create_dir(&"/dir".into())?;
+    fs.create_file(&"/dir/hello.txt".into())?;
+    fs.write_file(&"/dir/hello.txt".into(), &[0x61; 5000])?;
+    assert_eq!(fs.read_file(&"/dir/hello.txt".into())?, vec![0x61; 5000]);
+
+    // fatfs クレートからも FAT16 として読める
+    let fatfs = FatFs::new(Cursor::new(builder.build_bytes()?), FsOptions::new())?;
+    assert_eq!(fatfs.fat_type(), fatfs::FatType::Fat16);
+    fatfs.root_dir().create_file("a.txt")?.write_all(b"abc")?;
+    let mut data = vec![];
+    fatfs.root_dir().open_file("a.txt")?.read_to_end(&mut data)?;
+    assert_eq!(data, b"abc");
+
+    // クラスタ数が FAT16 の範囲に収まらない組み合わせ
+    assert!(Fat16Builder::new().total_sectors(4096).build().is_err());
+    assert!(Fat16Builder::new().total_sectors(262144).sectors_per_cluster(1).build().is_err());
+    assert!(Fat16Builder::new().sectors_per_cluster(3).build().is_err());
+    assert!(Fat16Builder::new().bytes_per_sector(768).build().is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn detect_fat_type() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, FatType};
+
+    // 境界値 (4085 未満は FAT12, 65525 未満は FAT16)
+    assert_eq!(FatType::from_cluster_count(4084), FatType::Fat12);
+    assert_eq!(FatType::from_cluster_count(4085), FatType::Fat16);
+    assert_eq!(FatType::from_cluster_count(65524), FatType::Fat16);
+    assert_eq!(FatType::from_cluster_count(65525), FatType::Fat32);
+
+    // テストイメージはクラスタ数から FAT16 と判定される
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    assert_eq!(fs.bpb.fat_type(), FatType::Fat16);
+
+    // file_system_type の文字列は判定に使わない
+    bytes[54..62].copy_from_slice(b"FAT12   ");
+    assert_eq!(Fat16::from_bytes(bytes)?.bpb.fat_type(), FatType::Fat16);
+
+    // FAT12 / FAT32 のイメージは FAT16 として開けない
+    let fat12 = format_with_files(fatfs::FatType::Fat12, 2 * 1024 * 1024)?;
+    let err = Fat16::from_bytes(fat12).unwrap_err();
+    assert_eq!(err.to_string(), "Not a FAT16 volume: detected Fat12 from cluster count");
+    let fat32 = format_with_files(fatfs::FatType::Fat32, 64 * 1024 * 1024)?;
+    let err = Fat16::from_bytes(fat32).unwrap_err();
+    assert_eq!(err.to_string(), "Not a FAT16 volume: detected Fat32 from sectors_per_fat = 0");
+
+    Ok(())
+}
+
+#[test]
+fn date_time_to_system_time() -> Result<(), Box<dyn StdError>> {
+    use std::time::{Duration, UNIX_EPOCH};
+    use fat16_test::{Fat16Date, Fat16Time};
+
+    // FAT のエポック (1980/01/01 00:00:00)
+    let epoch = Fat16Date::from(0x0021);
+    assert_eq!(epoch.duration_since_epoch(), Duration::from_secs(315532800));
+
+    // 2024/03/15 13:45:58 (秒のフィールドは 2 秒単位で 29)
+    let date = Fat16Date::from((44 << 9) | (3 << 5) | 15);
+    let time = Fat16Time::from((13 << 11) | (45 << 5) | 29);
+    let secs = date.to_system_time(&time).duration_since(UNIX_EPOCH)?;
+    assert_eq!(secs, Duration::from_secs(1710510358));
+
+    // 作成時刻の 10 ミリ秒単位の補正 (150 = 1.5 秒)
+    let time = Fat16Time::from(((13 << 11) | (45 << 5) | 29, 150));
+    let secs = date.to_system_time(&time).duration_since(UNIX_EPOCH)?;
+    assert_eq!(secs, Duration::from_millis(1710510359500));
+
+    Ok(())
+}
+
+#[test]
+fn touch() -> Result<(), Box<dyn StdError>> {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use fat16_test::{Fat16Builder, Fat16Date};
+
+    // create_file も write_file と同じく作成・更新日時を現在時刻にする
+    let before = SystemTime::now() - Duration::from_secs(2);
+    let mut fs = Fat16Builder::new().build()?;
+    fs.create_file(&"/a.txt".into())?;
+    let mut entry = fs.read_directory(&"/".into())?.remove(0);
+    assert!(entry.last_modify_date.to_system_time(&entry.last_modify_time) >= before);
+    assert!(entry.creation_date.to_system_time(&entry.creation_time) >= before);
+    assert_eq!(entry.last_access_date, entry.last_modify_date);
+
+    // 2024/03/15 13:45:59 は 2 秒単位に切り捨てて 13:45:58 になる
+    entry.touch(UNIX_EPOCH + Duration::from_secs(1710510359));
+    let encoded = (entry.last_modify_date.to_u16(), entry.last_modify_time.to_u16().0);
+    assert_eq!(encoded, (((44 << 9) | (3 << 5) | 15), ((13 << 11) | (45 << 5) | 29)));
+    assert_eq!(entry.last_access_date.to_u16(), encoded.0);
+    let decoded = Fat16Date::from(encoded.0).to_system_time(&encoded.1.into());
+    assert_eq!(decoded, UNIX_EPOCH + Duration::from_secs(1710510358));
+
+    // write_file は更新日時を現在時刻にする
+    fs.write_file(&"/a.txt".into(), b"abc")?;
+    let entry = fs.read_directory(&"/".into())?.remove(0);
+    assert!(entry.last_modify_date.to_system_time(&entry.last_modify_time) >= before);
+
+    Ok(())
+}
+
+#[cfg(feature = "sha2")]
+#[serial]
+#[test]
+fn open_verified() -> Result<(), Box<dyn StdError>> {
+    use sha2::{Digest, Sha256};
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let digest: [u8; 32] = Sha256::digest(fs::read(&img_path)?).into();
+
+    // ハッシュが一致すれば開ける
+    let fs = Fat16::open_verified(&img_path, digest)?;
+    assert_eq!(fs.read_file(&"/1.txt".into())?, b"No.1\n");
+
+    // 一致しなければエラー
+    let mut wrong = digest;
+    wrong[0] ^= 0xFF;
+    assert!(Fat16::open_verified(&img_path, wrong).is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "flate2")]
+#[serial]
+#[test]
+fn from_gz() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+    use flate2::{write::GzEncoder, Compression};
+
+    // テスト用イメージを gzip で圧縮する
+    let img_path = init_fat16()?;
+    let gz_path = format!("{}/target/tmp/fat16.img.gz", env::var("CARGO_MANIFEST_DIR")?);
+    let mut encoder = GzEncoder::new(File::create(&gz_path)?, Compression::default());
+    encoder.write_all(&fs::read(&img_path)?)?;
+    encoder.finish()?;
+
+    let fs = Fat16::from_gz(&gz_path)?;
+    assert_eq!(fs.read_file(&"/test_dir_3/long_1.txt".into())?, vec![0x61; 3000]);
+
+    // 圧縮されていないファイルはエラー
+    assert!(Fat16::from_gz(&img_path).is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono_modified() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16DirEntry;
+
+    // 更新日時 2024/03/15 13:45:58
+    let mut record = [0u8; 32];
+    record[0..11].copy_from_slice(b"1       TXT");
+    record[11] = 0x20;
+    record[22..24].copy_from_slice(&(((13 << 11) | (45 << 5) | 29) as u16).to_le_bytes());
+    record[24..26].copy_from_slice(&(((44 << 9) | (3 << 5) | 15) as u16).to_le_bytes());
+
+    let (entry, _) = Fat16DirEntry::parse_entry(&record)?;
+    let modified = entry.unwrap().modified();
+    assert_eq!(modified.format("%Y-%m-%dT%H:%M:%S").to_string(), "2024-03-15T13:45:58");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn all_files() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // ルート 3 + test_dir_1 3 + test_dir_1_1 3 + test_dir_2 3 + test_dir_3 3
+    let files = fs.all_files(&"/".into())?;
+    assert_eq!(files.len(), 15);
+    assert!(files.iter().all(|(_, entry)| !entry.is_dir() && !entry.is_volume_label()));
+    assert!(files.iter().any(|(path, _)| path.original() == "/test_dir_1/test_dir_1_1/3.txt"));
+
+    // サブツリーのみ
+    assert_eq!(fs.all_files(&"/test_dir_1".into())?.len(), 6);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn files() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // 全てのファイルのパスと内容 (ディレクトリ・ボリュームラベルは含まない)
+    let files = fs.files().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(files.len(), 15);
+    assert_eq!(files[0], ("/1.txt".to_string(), b"No.1\n".to_vec()));
+    let (_, long_1) = files.iter().find(|(path, _)| path == "/test_dir_3/long_1.txt").unwrap();
+    assert_eq!(*long_1, vec![0x61; 3000]);
+
+    Ok(())
+}
+
+#[test]
+fn encode_date_time() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16Date, Fat16Time};
+
+    // 有効な全ての日付で from(to_u16(x)) == x
+    for year in 0..128u16 {
+        for month in 1..=12u16 {
+            for day in 1..=31u16 {
+                let raw = (year << 9) | (month << 5) | day;
+                let date = Fat16Date::from(raw);
+                assert_eq!(date.to_u16(), raw);
+                assert_eq!(Fat16Date::from(date.to_u16()), date);
+            }
+        }
+    }
+
+    // 有効な全ての時刻 (10 ミリ秒単位の補正を含む) で from(to_u16(x)) == x
+    for hour in 0..24u16 {
+        for minute in 0..60u16 {
+            for half_second in 0..30u16 {
+                for tenths in [0, 99, 199] {
+                    let raw = ((hour << 11) | (minute << 5) | half_second, tenths);
+                    let time = Fat16Time::from(raw);
+                    assert_eq!(time.to_u16(), raw);
+                    assert_eq!(Fat16Time::from(time.to_u16()), time);
+                }
+            }
+        }
+    }
+
+    // 年は 1980 - 2107 に丸める
+    let date = Fat16Date { year: 1970, month: 1, day: 1 };
+    assert_eq!(date.to_u16(), 0x0021);
+    let date = Fat16Date { year: 2200, month: 12, day: 31 };
+    assert_eq!(Fat16Date::from(date.to_u16()).year, 2107);
+
+    Ok(())
+}
+
+#[test]
+fn checked_date_time() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16Date, Fat16Time};
+
+    // 正常な値
+    let date = Fat16Date::checked((44 << 9) | (3 << 5) | 15)?;
+    assert_eq!((date.year, date.month, date.day), (2024, 3, 15));
+    let time = Fat16Time::checked((13 << 11) | (45 << 5) | 29)?;
+    assert_eq!((time.hour, time.minute, time.second), (13, 45, 58));
+
+    // 月が 0 / 15, 日が 0
+    assert!(Fat16Date::checked((44 << 9) | 15).is_err());
+    assert!(Fat16Date::checked((44 << 9) | (15 << 5) | 15).is_err());
+    assert!(Fat16Date::checked((44 << 9) | (3 << 5)).is_err());
+
+    // 時が 31, 分が 63, 秒が 62
+    assert!(Fat16Time::checked(31 << 11).is_err());
+    assert!(Fat16Time::checked(63 << 5).is_err());
+    assert!(Fat16Time::checked(31).is_err());
+
+    // From はそのまま (変換のみ) 行う
+    assert_eq!(Fat16Time::from(31 << 11).hour, 31);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_file_untruncated() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+    let bytes_per_cluster = fs.bpb.bytes_per_sector as usize * fs.bpb.sectors_per_cluster as usize;
+
+    // クラスタ単位の長さで、先頭は通常の読み込みと一致する
+    for path in ["/1.txt", "/test_dir_3/long_1.txt"] {
+        let file = fs.read_file(&path.into())?;
+        let untruncated = fs.read_file_untruncated(&path.into())?;
+        assert_eq!(untruncated.len() % bytes_per_cluster, 0);
+        assert!(untruncated.len() > file.len());
+        assert_eq!(&untruncated[..file.len()], &file[..]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn format_long() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16DirEntry;
+
+    // READ_ONLY | ARCHIVE, 2024-05-06 12:34:56, 1234 バイト
+    let mut record = [0u8; 32];
+    record[0..11].copy_from_slice(b"README  TXT");
+    record[11] = 0x21;
+    record[22..24].copy_from_slice(&(((12 << 11) | (34 << 5) | 28) as u16).to_le_bytes());
+    record[24..26].copy_from_slice(&(((44 << 9) | (5 << 5) | 6) as u16).to_le_bytes());
+    record[28..32].copy_from_slice(&1234u32.to_le_bytes());
+    let (entry, _) = Fat16DirEntry::parse_entry(&record)?;
+    let entry = entry.unwrap();
+    assert_eq!(entry.format_long(), "R----A       1234 2024-05-06 12:34:56 README.TXT");
+
+    // ディレクトリ
+    record[11] = 0x10;
+    record[28..32].copy_from_slice(&0u32.to_le_bytes());
+    let (entry, _) = Fat16DirEntry::parse_entry(&record)?;
+    assert_eq!(entry.unwrap().format_long(), "----D-          0 2024-05-06 12:34:56 README.TXT");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn volume_label() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(&img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    assert_eq!(fs.volume_label(), "FAT16IMG");
+    assert_eq!(fs.fs_type(), "FAT16");
+
+    // ルートディレクトリのラベルが EBPB のラベルより優先される
+    patch_root_entry(&mut bytes, &fs, b"FAT16IMG   ", |entry| entry[0..11].copy_from_slice(b"RENAMED VOL"));
+    let fs = Fat16::from_bytes(bytes)?;
+    assert_eq!(fs.volume_label(), "RENAMED VOL");
+    assert_eq!(&fs.ebpb.volume_label, b"FAT16IMG   ");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn same_file() -> Result<(), Box<dyn StdError>> {
+    use fatfs::{FileSystem as FatFs, FsOptions};
+    use fat16_test::Fat16;
+
+    // 8.3 形式で表せない名前には "~1" 付きの短い名前が付けられる
+    let img_path = init_fat16()?;
+    {
+        let img_file = OpenOptions::new().read(true).write(true).open(&img_path)?;
+        let fatfs = FatFs::new(img_file, FsOptions::new())?;
+        let dir = fatfs.root_dir().open_dir("test_dir_3")?;
+        dir.create_file("long file 1.txt")?.write_all(b"alias\n")?;
+    }
+    let fs = Fat16::new(img_path)?;
+
+    // 8.3 形式の名前でも参照できる
+    let entries = fs.read_directory(&"/test_dir_3".into())?;
+    let entry = entries.iter().find(|e| e.name == "long file 1.txt").unwrap();
+    assert_eq!(entry.short_name, "LONGFI~1.TXT");
+    assert_eq!(fs.read_file(&"/test_dir_3/longfi~1.txt".into())?, b"alias\n");
+
+    // 長い名前・8.3 形式の名前・大文字小文字違いは同じファイル
+    assert!(fs.same_file(&"/test_dir_3/long file 1.txt".into(), &"/test_dir_3/LONGFI~1.TXT".into())?);
+    assert!(fs.same_file(&"/TEST_DIR_3/LONG_1.TXT".into(), &"/test_dir_3/long_1.txt".into())?);
+    assert!(!fs.same_file(&"/test_dir_3/long_1.txt".into(), &"/test_dir_3/long_2.txt".into())?);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn trailing_slash() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, Path};
+
+    // 末尾の '/' と空のパスは正規化される
+    for root in ["/", "", "//"] {
+        let path = Path::from(root);
+        assert!(path.is_root());
+        assert!(path.parse().is_empty());
+        assert_eq!(path.original(), "/");
+    }
+    let path = Path::from("/Test_Dir_1/");
+    assert_eq!(path.parse(), ["test_dir_1"]);
+    assert_eq!(path.original(), "/Test_Dir_1");
+    assert_eq!(path.file_name(), "Test_Dir_1");
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+    let names = |entries: Vec<fat16_test::Fat16DirEntry>| entries.into_iter().map(|e| e.name).collect::<Vec<_>>();
+    assert_eq!(names(fs.read_directory(&"/test_dir_1/".into())?), names(fs.read_directory(&"/test_dir_1".into())?));
+    assert_eq!(fs.read_file(&"/test_dir_2/1.txt/".into())?, b"No.2-1\n");
+    assert_eq!(fs.canonicalize(&"/".into())?, "/");
+    assert!(fs.is_dir(&"".into())?);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn path_conversions() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, Path};
+
+    // String / &String / std::path::Path から変換できる
+    let owned = String::from("/Test_Dir_1/1.txt");
+    assert_eq!(Path::from(&owned).original(), "/Test_Dir_1/1.txt");
+    assert_eq!(Path::from(owned).parse(), ["test_dir_1", "1.txt"]);
+    let std_path = std::path::Path::new("test_dir_1/test_dir_1_1");
+    assert_eq!(Path::from(std_path).original(), "/test_dir_1/test_dir_1_1");
+
+    // '\' も区切り文字として扱う
+    assert_eq!(Path::from(std::path::Path::new("\\test_dir_1\\1.txt")).parse(), ["test_dir_1", "1.txt"]);
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+    assert_eq!(fs.read_file(&format!("/test_dir_{}/1.txt", 2).into())?, b"No.2-1\n");
+    assert_eq!(fs.read_file(&std::path::Path::new("/test_dir_1/2.txt").into())?, b"No.1-2\n");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_root_directory() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    let entries = fs.read_directory(&"/".into())?;
+    let summary = |entries: &[fat16_test::Fat16DirEntry]| entries.iter().map(|e| (e.name.clone(), e.first_cluster)).collect::<Vec<_>>();
+    assert_eq!(summary(&entries), summary(&fs.root_dir));
+    assert!(entries.iter().any(|e| e.name == "test_dir_3"));
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn open_dir() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // / から test_dir_1/test_dir_1_1 へ移動して一覧を取得する
+    let root = fs.open_dir(&"/".into())?;
+    assert!(root.entries()?.iter().any(|e| e.name == "test_dir_1"));
+    let dir = root.cd("test_dir_1")?.cd("TEST_DIR_1_1")?;
+    assert_eq!(dir.path().original(), "/test_dir_1/test_dir_1_1");
+    assert!(dir.entries()?.iter().any(|e| e.name == "2.txt"));
+
+    // 親ディレクトリへ戻る
+    let parent = dir.parent()?;
+    assert_eq!(parent.path().original(), "/test_dir_1");
+    assert!(parent.entries()?.iter().any(|e| e.name == "test_dir_1_1"));
+    assert_eq!(dir.cd("..")?.cd("..")?.path().original(), "/");
+    assert!(root.parent()?.path().is_root());
+
+    // ファイルや存在しない名前には移動できない
+    assert!(root.cd("1.txt").is_err());
+    assert!(root.cd("no_such_dir").is_err());
+    assert!(fs.open_dir(&"/1.txt".into()).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn copy_file() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+
+    // 同じディレクトリ内へのコピー
+    fs.copy_file(&"/1.txt".into(), &"/1_copy.txt".into())?;
+    assert_eq!(fs.read_file(&"/1_copy.txt".into())?, fs.read_file(&"/1.txt".into())?);
+
+    // 日時・属性を引き継ぎ、クラスタは別に確保される
+    let entries = fs.read_directory(&"/".into())?;
+    let src = entries.iter().find(|e| e.name == "1.txt").unwrap();
+    let dst = entries.iter().find(|e| e.name == "1_copy.txt").unwrap();
+    assert_eq!(dst.attribute, src.attribute);
+    assert_eq!(dst.last_modify_date, src.last_modify_date);
+    assert_eq!(dst.last_modify_time, src.last_modify_time);
+    assert_eq!(dst.creation_date, src.creation_date);
+    assert_ne!(dst.first_cluster, src.first_cluster);
+
+    // 別のディレクトリへのコピー (複数クラスタ)
+    fs.copy_file(&"/test_dir_3/long_1.txt".into(), &"/test_dir_1/long_copy.txt".into())?;
+    assert_eq!(fs.read_file(&"/test_dir_1/long_copy.txt".into())?, vec![0x61; 3000]);
+    fs.verify_all_chains()?;
+
+    // コピー先が既に存在する場合・ディレクトリはコピーできない
+    assert!(fs.copy_file(&"/2.txt".into(), &"/1_COPY.TXT".into()).is_err());
+    assert!(fs.copy_file(&"/test_dir_1".into(), &"/dir_copy".into()).is_err());
+    assert_eq!(fs.read_file(&"/1_copy.txt".into())?, b"No.1\n");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn fs_functions() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+    use fat16_test::fs as fat_fs;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // std::fs::read / read_to_string
+    assert_eq!(fat_fs::read(&fs, "/1.txt")?, b"No.1\n");
+    assert_eq!(fat_fs::read_to_string(&fs, String::from("/test_dir_1/2.txt"))?, "No.1-2\n");
+    assert!(fat_fs::read(&fs, "/no_such_file").is_err());
+
+    // std::fs::read_dir は . と .. を含まず、ファイルに対してはエラー
+    let names = fat_fs::read_dir(&fs, "/test_dir_1")?.into_iter().map(|e| e.name).collect::<Vec<_>>();
+    assert_eq!(names, ["1.txt", "2.txt", "3.txt", "test_dir_1_1"]);
+    assert!(fat_fs::read_dir(&fs, "/1.txt").is_err());
+
+    // std::fs::metadata
+    let metadata = fat_fs::metadata(&fs, "/test_dir_3/long_1.txt")?;
+    assert!(metadata.is_file() && !metadata.is_dir());
+    assert_eq!(metadata.len(), 3000);
+    assert!(!metadata.readonly());
+    assert!(metadata.modified()? > std::time::UNIX_EPOCH);
+    let metadata = fat_fs::metadata(&fs, "/test_dir_3")?;
+    assert!(metadata.is_dir() && !metadata.is_file());
+    let metadata = fat_fs::metadata(&fs, "/")?;
+    assert!(metadata.is_dir() && metadata.modified().is_err());
+    assert!(fat_fs::metadata(&fs, "/no_such_file").is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn zero_first_cluster() -> Result<(), Box<dyn StdError>> {
+    use fatfs::{FileSystem as FatFs, FsOptions};
+    use fat16_test::Fat16;
+
+    // fatfs で空のファイルを作成する (クラスタは割り当てられない)
+    let img_path = init_fat16()?;
+    {
+        let img_file = OpenOptions::new().read(true).write(true).open(&img_path)?;
+        let fatfs = FatFs::new(img_file, FsOptions::new())?;
+        fatfs.root_dir().create_file("empty.txt")?;
+    }
+
+    let mut bytes = fs::read(&img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let path = "/empty.txt".into();
+    assert_eq!(fs.read_directory(&"/".into())?.iter().find(|e| e.name == "empty.txt").unwrap().first_cluster, 0);
+    assert!(fs.read_file(&path)?.is_empty());
+    assert!(fs.read_file_verify_order(&path)?.is_empty());
+    assert!(fs.read_file_tail(&path, 10)?.is_empty());
+    assert!(fs.read_file_range(&path, 0, 10)?.is_empty());
+    assert!(fs.locate(&path)?.is_empty());
+    assert!(fs.alloc_table.get_cluster_chain(0)?.is_empty());
+
+    // 先頭クラスタが 0 のディレクトリは空
+    patch_root_entry(&mut bytes, &fs, b"TEST_D~2   ", |entry| entry[26..28].copy_from_slice(&0u16.to_le_bytes()));
+    let fs = Fat16::from_bytes(bytes)?;
+    assert!(fs.read_directory(&"/test_dir_2".into())?.is_empty());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn exists() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    assert!(fs.exists(&"/1.txt".into()));
+    assert!(fs.exists(&"/test_dir_1/test_dir_1_1/3.txt".into()));
+    assert!(!fs.exists(&"/nope.txt".into()));
+    assert!(!fs.exists(&"/nope/1.txt".into()));
+
+    // ファイル・ディレクトリの判定
+    assert!(fs.is_file(&"/1.txt".into())?);
+    assert!(!fs.is_dir(&"/1.txt".into())?);
+    assert!(fs.is_dir(&"/test_dir_1".into())?);
+    assert!(fs.is_dir(&"/".into())?);
+    assert!(fs.is_file(&"/nope.txt".into()).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn reserved_region() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let bytes = fs::read(&img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let bytes_per_sector = fs.bpb.bytes_per_sector as usize;
+    assert_eq!(fs.reserved_region().len(), fs.bpb.reserved_sector_count as usize * bytes_per_sector);
+    assert_eq!(fs.reserved_region(), &bytes[..fs.reserved_region().len()]);
+
+    // ブートセクタの後ろに 3 セクタ (ブートローダなど) を挿入する
+    let loader = vec![0xAB; 3 * bytes_per_sector];
+    let mut shifted = bytes[..bytes_per_sector].to_vec();
+    shifted.extend(&loader);
+    shifted.extend(&bytes[bytes_per_sector..]);
+    let reserved_sector_count = fs.bpb.reserved_sector_count + 3;
+    shifted[14..16].copy_from_slice(&reserved_sector_count.to_le_bytes());
+    let large_sectors = fs.bpb.large_sectors + 3;
+    shifted[32..36].copy_from_slice(&large_sectors.to_le_bytes());
+
+    // 予約領域を正しく読み飛ばしてパースできる
+    let fs = Fat16::from_bytes(shifted.clone())?;
+    assert_eq!(fs.reserved_region().len(), 4 * bytes_per_sector);
+    assert_eq!(&fs.reserved_region()[bytes_per_sector..], &loader[..]);
+    assert_eq!(fs.read_file(&"/test_dir_1/test_dir_1_1/3.txt".into())?, b"No.1-1-3\n");
+    let fs = Fat16::from_reader(std::io::Cursor::new(shifted))?;
+    assert_eq!(fs.read_file(&"/test_dir_1/test_dir_1_1/3.txt".into())?, b"No.1-1-3\n");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_file_range() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    let path = "/test_dir_3/long_1.txt".into();
+    assert_eq!(fs.read_file_range(&path, 1000, 10)?, vec![0x61; 10]);
+
+    // クラスタ境界 (2048) をまたぐ範囲
+    let data = fs.read_file(&path)?;
+    assert_eq!(fs.read_file_range(&path, 2040, 16)?, &data[2040..2056]);
+
+    // file_size で切り詰める
+    assert_eq!(fs.read_file_range(&path, 2990, 100)?.len(), 10);
+    assert!(fs.read_file_range(&path, 5000, 10)?.is_empty());
+    assert_eq!(fs.read_file_range(&"/1.txt".into(), 3, 10)?, b"1\n");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_file_range_short_chain() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // long_1.txt (3000 バイト) のチェーンを 1 クラスタ目で終わらせる
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let entry = fs.read_directory(&"/test_dir_3".into())?.into_iter().find(|e| e.name == "long_1.txt").unwrap();
+    set_fat_entry(&mut bytes, &fs, entry.first_cluster as u16, 0xFFFF);
+    let fs = Fat16::from_bytes(bytes)?;
+
+    // 2 クラスタ目の範囲はパニックせずエラーになる
+    let path = "/test_dir_3/long_1.txt".into();
+    assert!(fs.read_file_range(&path, 2500, 10).is_err());
+    assert!(fs.read_file_range(&path, 2040, 16).is_err());
+    assert_eq!(fs.read_file_range(&path, 1000, 10)?, vec![0x61; 10]);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn resolve_chain() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    let chain = fs.resolve_chain(&"/test_dir_1/test_dir_1_1/2.txt".into())?;
+    let names = chain.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["test_dir_1", "test_dir_1_1", "2.txt"]);
+    assert!(chain[0].is_dir() && chain[1].is_dir() && !chain[2].is_dir());
+
+    // 途中の要素が見つからない場合はエラー
+    assert!(fs.resolve_chain(&"/test_dir_1/nope/2.txt".into()).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn read_entry() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // 一覧で得た DirEntry をそのまま読む
+    let entries = fs.read_directory(&"/test_dir_1".into())?;
+    let entry = entries.iter().find(|e| e.name == "1.txt").unwrap();
+    assert_eq!(fs.read_entry(entry)?, b"No.1-1\n");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn create_dir() -> Result<(), Box<dyn StdError>> {
+    use std::time::{Duration, SystemTime};
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+
+    // ルート直下 (固定サイズの領域) に作成
+    fs.create_dir(&"/new_dir".into())?;
+    let new_dir = fs.root_dir.iter().find(|e| e.name == "new_dir").unwrap().clone();
+    assert!(new_dir.is_dir());
+    assert_eq!(fs.alloc_table.get_checked_cluster_chain(new_dir.first_cluster as u16)?, vec![new_dir.first_cluster as u16]);
+
+    // . は自身、.. はルート (0) を指す
+    let entries = fs.read_directory(&"/new_dir".into())?;
+    let dots = entries.iter().map(|e| (e.name.as_str(), e.first_cluster)).collect::<Vec<_>>();
+    assert_eq!(dots, [(".", new_dir.first_cluster), ("..", 0)]);
+
+    // サブディレクトリ (クラスタ) の中に作成すると .. は親のクラスタを指す
+    fs.create_dir(&"/new_dir/child".into())?;
+    let entries = fs.read_directory(&"/new_dir/child".into())?;
+    assert_eq!(entries[1].name, "..");
+    assert_eq!(entries[1].first_cluster, new_dir.first_cluster);
+
+    // 作成・更新日時は write_file と同じく現在時刻になる
+    let elapsed = SystemTime::now().duration_since(new_dir.last_modify_date.to_system_time(&new_dir.last_modify_time))?;
+    assert!(elapsed < Duration::from_secs(60), "{:?}", elapsed);
+    assert_eq!(new_dir.creation_date, new_dir.last_modify_date);
+    assert_eq!(new_dir.creation_time.to_u16().0, new_dir.last_modify_time.to_u16().0);
+
+    // 同じ名前は作成できない
+    assert!(fs.create_dir(&"/new_dir".into()).is_err());
+    assert!(fs.create_dir(&"/1.txt".into()).is_err());
+    assert!(fs.create_dir(&"/nope/child".into()).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn write_file_zero_fill_slack() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+    let bytes_per_cluster = fs.bpb.bytes_per_sector as usize * fs.bpb.sectors_per_cluster as usize;
+
+    // 3000 バイトの 'a' が入っていたクラスタに短いデータを書く
+    fs.write_file(&"/test_dir_3/long_1.txt".into(), b"short")?;
+    assert_eq!(fs.read_file(&"/test_dir_3/long_1.txt".into())?, b"short");
+    let untruncated = fs.read_file_untruncated(&"/test_dir_3/long_1.txt".into())?;
+    assert_eq!(untruncated.len(), bytes_per_cluster);
+    assert!(untruncated[5..].iter().all(|b| *b == 0));
+
+    // ゼロ埋めを無効にすると以前のデータが残る
+    fs.set_zero_fill_slack(false);
+    fs.write_file(&"/test_dir_3/long_2.txt".into(), b"short")?;
+    let untruncated = fs.read_file_untruncated(&"/test_dir_3/long_2.txt".into())?;
+    assert!(untruncated[5..].iter().all(|b| *b == 0x62));
+    fs.set_zero_fill_slack(true);
+
+    // 新しいファイルの作成・書き込み・長さの変更
+    fs.create_file(&"/test_dir_3/New File.txt".into())?;
+    assert_eq!(fs.read_file(&"/test_dir_3/new file.txt".into())?, b"");
+    fs.write_file(&"/test_dir_3/New File.txt".into(), &[0x64; 5000])?;
+    assert_eq!(fs.read_file(&"/test_dir_3/New File.txt".into())?, vec![0x64; 5000]);
+    fs.set_len(&"/test_dir_3/New File.txt".into(), 10)?;
+    fs.set_len(&"/test_dir_3/New File.txt".into(), 20)?;
+    let mut expected = vec![0x64; 10];
+    expected.extend([0; 10]);
+    assert_eq!(fs.read_file(&"/test_dir_3/New File.txt".into())?, expected);
+    let entry = fs.resolve_chain(&"/test_dir_3/New File.txt".into())?.pop().unwrap();
+    assert_eq!(fs.alloc_table.get_checked_cluster_chain(entry.first_cluster as u16)?.len(), 1);
+    fs.set_len(&"/test_dir_3/New File.txt".into(), 0)?;
+    assert_eq!(fs.resolve_chain(&"/test_dir_3/New File.txt".into())?.pop().unwrap().first_cluster, 0);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn root_capacity() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+    assert_eq!(fs.root_capacity(), 512);
+
+    // 8.3 形式に収まる名前は SFN の 1 スロット、長い名前は LFN の分も使う
+    let free = fs.root_free_slots();
+    assert!(free < 512);
+    fs.create_file(&"/NEW.TXT".into())?;
+    assert_eq!(fs.root_free_slots(), free - 1);
+    fs.create_file(&"/a long file name.txt".into())?;
+    assert_eq!(fs.root_free_slots(), free - 1 - 3);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn entry_counts() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+
+    // 1.txt - 3.txt, test_dir_1 - 3, ボリュームラベル
+    assert_eq!(fs.root_used_entries(), 7);
+    assert_eq!(fs.file_count(&"/".into())?, 3);
+    assert_eq!(fs.file_count(&"/test_dir_1".into())?, 3);
+    assert_eq!(fs.file_count(&"/test_dir_1/test_dir_1_1".into())?, 3);
+
+    // 削除済みエントリは数えない
+    fs.remove_file(&"/2.txt".into())?;
+    assert_eq!(fs.root_used_entries(), 6);
+    assert_eq!(fs.file_count(&"/".into())?, 2);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn remove_file() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+    let free_clusters = fs.allocation_bitmap().iter().filter(|used| !**used).count();
+    let free_slots = fs.root_free_slots();
+
+    // 作成して削除すると、クラスタとスロットが元に戻る
+    fs.create_file(&"/to be removed.txt".into())?;
+    fs.write_file(&"/to be removed.txt".into(), &[0x65; 5000])?;
+    assert_ne!(fs.allocation_bitmap().iter().filter(|used| !**used).count(), free_clusters);
+    fs.remove_file(&"/to be removed.txt".into())?;
+    assert_eq!(fs.allocation_bitmap().iter().filter(|used| !**used).count(), free_clusters);
+    assert_eq!(fs.root_free_slots(), free_slots);
+    assert!(!fs.exists(&"/to be removed.txt".into()));
+    assert!(fs.read_file(&"/to be removed.txt".into()).is_err());
+
+    // ディレクトリは削除できない
+    assert!(fs.remove_file(&"/test_dir_1".into()).is_err());
+
+    // 読み取り専用のファイルは force_remove_file でのみ削除できる
+    let mut bytes = fs::read(init_fat16()?)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    patch_root_entry(&mut bytes, &fs, b"1       TXT", |entry| entry[11] |= 0x01);
+    let mut fs = Fat16::from_bytes(bytes)?;
+    assert!(fs.remove_file(&"/1.txt".into()).is_err());
+    fs.force_remove_file(&"/1.txt".into())?;
+    assert!(!fs.exists(&"/1.txt".into()));
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn deleted_entries() -> Result<(), Box<dyn StdError>> {
+    use fatfs::{FileSystem as FatFs, FsOptions};
+    use fat16_test::{Fat16, Fat16DirEntry};
+
+    // fatfs でファイルを作成してから削除する
+    let img_path = init_fat16()?;
+    {
+        let img_file = OpenOptions::new().read(true).write(true).open(&img_path)?;
+        let fatfs = FatFs::new(img_file, FsOptions::new())?;
+        let dir = fatfs.root_dir().open_dir("test_dir_2")?;
+        dir.create_file("hello.txt")?.write_all(b"hello, world\n")?;
+        dir.remove("hello.txt")?;
+    }
+
+    // 通常の一覧には現れない
+    let fs = Fat16::new(&img_path)?;
+    assert!(fs.read_directory(&"/test_dir_2".into())?.iter().all(|e| e.name != "_ELLO.TXT"));
+
+    // 削除済みエントリとしてサイズ・先頭クラスタを参照できる
+    let deleted = fs.deleted_entries(&"/test_dir_2".into())?;
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0].name, "_ELLO.TXT");
+    assert_eq!(deleted[0].file_size, 13);
+    assert_ne!(deleted[0].first_cluster, 0);
+
+    // パース時にまとめて返すこともできる
+    let mut records = [0u8; 64];
+    records[0..11].copy_from_slice(b"\xE5EMOVED TXT");
+    records[11] = 0x20;
+    records[28..32].copy_from_slice(&42u32.to_le_bytes());
+    records[32..43].copy_from_slice(b"ALIVE   TXT");
+    records[43] = 0x20;
+    let (entries, _) = Fat16DirEntry::parses_with_deleted(&records, 2)?;
+    let names = entries.iter().map(|e| (e.name.as_str(), e.file_size)).collect::<Vec<_>>();
+    assert_eq!(names, [("_EMOVED.TXT", 42), ("ALIVE.TXT", 0)]);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn rename() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+
+    // 同じディレクトリ内での名前の変更
+    let before = fs.resolve_chain(&"/1.txt".into())?.pop().unwrap();
+    fs.rename(&"/1.txt".into(), &"/renamed.txt".into())?;
+    assert!(!fs.exists(&"/1.txt".into()));
+    assert_eq!(fs.read_file(&"/renamed.txt".into())?, b"No.1\n");
+    let after = fs.resolve_chain(&"/renamed.txt".into())?.pop().unwrap();
+    assert_eq!((after.first_cluster, after.file_size), (before.first_cluster, before.file_size));
+    assert_eq!(after.last_modify_date.year, before.last_modify_date.year);
+
+    // 別のディレクトリへ移動し、8.3 形式に収まらない名前には "~1" を付ける
+    fs.rename(&"/test_dir_2/2.txt".into(), &"/test_dir_1/A Long Moved Name.txt".into())?;
+    let moved = fs.resolve_chain(&"/test_dir_1/a long moved name.txt".into())?.pop().unwrap();
+    assert_eq!(moved.short_name, "ALONGM~1.TXT");
+    assert_eq!(fs.read_file(&"/test_dir_1/ALONGM~1.TXT".into())?, b"No.2-2\n");
+    assert!(!fs.exists(&"/test_dir_2/2.txt".into()));
+
+    // 移動先が既に存在する場合はエラー
+    assert!(fs.rename(&"/2.txt".into(), &"/3.txt".into()).is_err());
+    assert_eq!(fs.read_file(&"/2.txt".into())?, b"No.2\n");
+
+    // ディレクトリを移動すると .. が新しい親を指す
+    fs.rename(&"/test_dir_2".into(), &"/test_dir_1/moved_dir".into())?;
+    let parent = fs.resolve_chain(&"/test_dir_1".into())?.pop().unwrap();
+    let entries = fs.read_directory(&"/test_dir_1/moved_dir".into())?;
+    assert_eq!(entries[1].first_cluster, parent.first_cluster);
+    assert_eq!(fs.read_file(&"/test_dir_1/moved_dir/3.txt".into())?, b"No.2-3\n");
+    assert!(fs.rename(&"/test_dir_1".into(), &"/test_dir_1/moved_dir/loop".into()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn rename_into_full_root() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16Builder;
+
+    let mut fs = Fat16Builder::new().root_entry_count(16).build()?;
+    fs.create_dir(&"/d".into())?;
+    fs.create_file(&"/d/keep.txt".into())?;
+    fs.write_file(&"/d/keep.txt".into(), b"keep")?;
+
+    // ルートディレクトリの空きスロットを使い切る
+    let mut n = 0;
+    while fs.create_file(&format!("/F{}.TXT", n).into()).is_ok() {
+        n += 1;
+    }
+    assert_eq!(fs.root_free_slots(), 0);
+
+    // 移動先に書き込めなかった場合は元のファイルが残る
+    let err = fs.rename(&"/d/keep.txt".into(), &"/k.txt".into()).unwrap_err();
+    assert_eq!(err.to_string(), "Root directory is full");
+    assert!(!fs.exists(&"/k.txt".into()));
+    assert_eq!(fs.read_file(&"/d/keep.txt".into())?, b"keep");
+    assert!(fs.find_lost_clusters()?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn lfn_encode() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{lfn, Fat16DirEntry};
+
+    // 13 文字を超える Unicode の名前 (BMP 外の文字はサロゲートペア)
+    let long_name = "長いファイル名のテスト 🎉 ümlaut.txt";
+    let short_name = lfn::generate_short_name(long_name, &[]);
+    assert_eq!(&short_name, b"______~1TXT");
+
+    let records = lfn::encode(long_name, &short_name);
+    assert_eq!(records.len(), long_name.encode_utf16().count().div_ceil(13));
+    assert_eq!(records[0][0] & 0x40, 0x40);
+    assert_eq!(records.last().unwrap()[0], 0x01);
+    assert!(records.iter().all(|record| record[11] == 0x0f && record[13] == lfn::checksum(&short_name)));
+
+    // SFN を後ろに付けてパースすると元の名前に戻る
+    let mut bytes = records.concat();
+    let mut sfn = [0u8; 32];
+    sfn[0..11].copy_from_slice(&short_name);
+    sfn[11] = 0x20;
+    bytes.extend(sfn);
+    let (entry, _) = Fat16DirEntry::parse_entry(&bytes)?;
+    assert_eq!(entry.unwrap().name, long_name);
+
+    // 既存の短い名前と衝突しない番号を選ぶ
+    let taken = [*b"LONGFI~1TXT", *b"LONGFI~2TXT"];
+    assert_eq!(&lfn::generate_short_name("long file.txt", &taken), b"LONGFI~3TXT");
+    assert_eq!(&lfn::generate_short_name("README.md", &[]), b"README  MD ");
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn truncated_fat() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+
+    // FAT 領域の途中でイメージを切り詰める
+    let reserved_size = fs.bpb.reserved_sector_count as usize * fs.bpb.bytes_per_sector as usize;
+    bytes.truncate(reserved_size + 1000);
+    let err = Fat16::from_bytes(bytes).unwrap_err();
+    assert!(err.to_string().contains("FAT region is truncated"), "{}", err);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn inflated_root_entry_count() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    // データ領域を切り落とし、ルートディレクトリ領域の直後でイメージを終わらせる
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    bytes.truncate(fs.data_region_offset());
+
+    // root_entry_count (0x11) を残りのバイト数より大きくする
+    bytes[0x11..0x13].copy_from_slice(&1024u16.to_le_bytes());
+    let err = Fat16::from_bytes(bytes).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "root_entry_count (1024) exceeds image size: root directory needs 32768 bytes, but only 16384 bytes remain after the FAT",
+    );
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn invalid_fat_layout() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, Fat16AllocTable, Fat16BPB};
+
+    let img_path = init_fat16()?;
+    let bytes = fs::read(img_path)?;
+
+    // num_fats (0x10) が 0
+    let mut broken = bytes.clone();
+    broken[0x10] = 0;
+    let err = Fat16::from_bytes(broken).unwrap_err();
+    assert!(err.to_string().contains("Invalid num_fats: 0"), "{}", err);
+
+    // sectors_per_fat (0x16) が 0 の BPB は FAT32 のものとして扱う
+    let mut broken = bytes.clone();
+    broken[0x16..0x18].copy_from_slice(&0u16.to_le_bytes());
+    let err = Fat16::from_bytes(broken.clone()).unwrap_err();
+    assert_eq!(err.to_string(), "Not a FAT16 volume: detected Fat32 from sectors_per_fat = 0");
+    let (bpb, _) = Fat16BPB::parse(&broken)?;
+    let err = Fat16AllocTable::parse(&broken[512..], &bpb).unwrap_err();
+    assert!(err.to_string().contains("Invalid sectors_per_fat"), "{}", err);
+
+    // bytes_per_sector (0x0B) が 2 のべき乗でない
+    let mut broken = bytes.clone();
+    broken[0x0B..0x0D].copy_from_slice(&768u16.to_le_bytes());
+    let err = Fat16::from_bytes(broken).unwrap_err();
+    assert!(err.to_string().contains("Invalid bytes_per_sector"), "{}", err);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn zero_bytes_per_sector() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, Fat16BPB, FatVolume};
+
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+
+    // bytes_per_sector (0x0B) が 0 の BPB はパースの時点でエラーになる
+    bytes[0x0B..0x0D].copy_from_slice(&0u16.to_le_bytes());
+    let err = Fat16BPB::parse(&bytes).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid bytes_per_sector: 0 (must be a power of two between 512 and 4096)");
+    assert!(Fat16::from_bytes(bytes.clone()).is_err());
+    assert!(FatVolume::from_bytes(bytes).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn invalid_sectors_per_cluster() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let bytes = fs::read(&img_path)?;
+
+    // sectors_per_cluster (オフセット 13) が 0 や 2 のべき乗でない値
+    for sectors_per_cluster in [0, 3, 96] {
+        let mut broken = bytes.clone();
+        broken[13] = sectors_per_cluster;
+        let err = Fat16::from_bytes(broken).unwrap_err();
+        assert!(err.to_string().contains("sectors_per_cluster"));
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn block_device() -> Result<(), Box<dyn StdError>> {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use fat16_test::{BlockDevice, Fat16};
+
+    // セクタ単位で読む RAM ディスク (読んだセクタ数を数える)
+    struct RamDisk {
+        bytes: Vec<u8>,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl BlockDevice for RamDisk {
+        fn read_sector(&self, lba: u64, buf: &mut [u8]) -> Result<(), Box<dyn StdError>> {
+            let head = lba as usize * 512;
+            buf.copy_from_slice(self.bytes.get(head..head + buf.len()).ok_or("out of range")?);
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        fn sector_count(&self) -> u64 {
+            (self.bytes.len() / 512) as u64
+        }
+    }
+
+    // 読み込むのはメタデータ領域と、読んだファイルのクラスタのみ
+    let img_path = init_fat16()?;
+    let bytes = fs::read(&img_path)?;
+    let reads = Arc::new(AtomicUsize::new(0));
+    let disk = RamDisk { bytes: bytes.clone(), reads: reads.clone() };
+    let fs = Fat16::from_device(disk)?;
+    assert_eq!(fs.read_file(&"/test_dir_2/3.txt".into())?, b"No.2-3\n");
+    assert!(fs.clusters.is_empty());
+    assert!(reads.load(Ordering::Relaxed) < 2 * fs.image_geometry().metadata_bytes as usize / 512);
+    assert_eq!(fs.image_geometry().image_bytes, bytes.len() as u64);
+
+    // Vec<u8> ・ファイルもブロックデバイスとして開ける
+    let fs = Fat16::from_device(bytes)?;
+    assert_eq!(fs.read_file(&"/test_dir_1/test_dir_1_1/1.txt".into())?, b"No.1-1-1\n");
+    let fs = Fat16::open(&img_path)?;
+    assert_eq!(fs.read_file(&"/test_dir_3/long_2.txt".into())?, vec![0x62; 3000]);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn cluster_chain_cache() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{ChainError, Fat16, Fat16Error};
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+
+    // キャッシュされたチェーンは毎回辿ったものと一致する
+    for (_, entry) in fs.walk(&"/".into())? {
+        if entry.first_cluster == 0 {
+            continue;
+        }
+        let first = entry.first_cluster as u16;
+        let fresh = fs.alloc_table.get_checked_cluster_chain(first)?;
+        assert_eq!(fs.alloc_table.get_cluster_chain(first)?, fresh);
+        assert_eq!(fs.alloc_table.get_cluster_chain(first)?, fresh);
+    }
+
+    // 書き込むとキャッシュが破棄される
+    let first = fs.resolve_chain(&"/test_dir_3/long_1.txt".into())?.pop().unwrap().first_cluster as u16;
+    assert_eq!(fs.alloc_table.get_cluster_chain(first)?.len(), 2);
+    fs.write_file(&"/test_dir_3/long_1.txt".into(), b"short")?;
+    assert_eq!(fs.alloc_table.get_cluster_chain(first)?, vec![first]);
+    assert_eq!(fs.read_file(&"/test_dir_3/long_1.txt".into())?, b"short");
+
+    // 壊れたチェーンは panic・無限ループせずにエラーになる
+    let img_path = init_fat16()?;
+    let bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let first = fs.resolve_chain(&"/test_dir_3/long_1.txt".into())?.pop().unwrap().first_cluster as u16;
+    for (next, expected) in [
+        (0x0000, ChainError::FreeCluster(first)),
+        (0xFFF0, ChainError::OutOfRange(0xFFF0)),
+        (0xFFF7, ChainError::BadCluster(first)),
+        (first, ChainError::Cycle(first)),
+    ] {
+        let mut bytes = bytes.clone();
+        set_fat_entry(&mut bytes, &fs, first, next);
+        let fs = Fat16::from_bytes(bytes)?;
+        assert_eq!(fs.alloc_table.get_cluster_chain(first), Err(expected.clone()));
+        let err = fs.read_file(&"/test_dir_3/long_1.txt".into()).unwrap_err();
+        match err.downcast_ref::<Fat16Error>() {
+            Some(Fat16Error::BrokenChain { path, error }) => {
+                assert_eq!(path, "/test_dir_3/long_1.txt");
+                assert_eq!(*error, expected);
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn find_nested_entry() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // 深いパス・ルート直下・存在しないパス
+    assert_eq!(fs.read_file(&"/test_dir_1/test_dir_1_1/2.txt".into())?, b"No.1-1-2\n");
+    assert_eq!(fs.read_file(&"/TEST_DIR_1/Test_Dir_1_1/2.TXT".into())?, b"No.1-1-2\n");
+    assert_eq!(fs.read_file(&"/2.txt".into())?, b"No.2\n");
+    assert!(fs.read_file(&"/test_dir_1/test_dir_1_1/4.txt".into()).is_err());
+    assert!(fs.read_file(&"/test_dir_1/1.txt/2.txt".into()).is_err());
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn glob() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+    let names = |entries: Vec<fat16_test::Fat16DirEntry>| entries.into_iter().map(|e| e.name).collect::<Vec<_>>();
+
+    assert_eq!(names(fs.glob(&"/test_dir_1".into(), "*.txt")?), ["1.txt", "2.txt", "3.txt"]);
+    assert_eq!(names(fs.glob(&"/test_dir_3".into(), "long_?.txt")?), ["long_1.txt", "long_2.txt", "long_3.txt"]);
+    assert_eq!(names(fs.glob(&"/".into(), "TEST_DIR_?")?), ["test_dir_1", "test_dir_2", "test_dir_3"]);
+    assert_eq!(names(fs.glob(&"/test_dir_1".into(), "*")?).len(), 4);
+    assert!(fs.glob(&"/test_dir_3".into(), "long_??.txt")?.is_empty());
+    assert_eq!(names(fs.glob(&"/".into(), "*1*")?), ["1.txt", "test_dir_1"]);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn tree() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    let rendered = fs.tree(&"/".into())?;
+    let lines = rendered.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "/");
+
+    // test_dir_1_1 は test_dir_1 の 1 段下にある
+    let dir_1 = lines.iter().position(|l| l.starts_with("├── test_dir_1 ")).unwrap();
+    let dir_1_1 = lines.iter().position(|l| l.starts_with("│   └── test_dir_1_1 ")).unwrap();
+    assert!(dir_1 < dir_1_1);
+    assert_eq!(lines[dir_1_1 + 1], "│       ├── 1.txt (attr: , size: 9, first_cluster: 10)");
+
+    // 最後のエントリ
+    assert!(lines.last().unwrap().starts_with("    └── long_3.txt"));
+    assert!(lines.iter().any(|l| l.contains("long_1.txt") && l.contains("size: 3000")));
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn extract_to() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // 出力先を用意する
+    let dest = format!("{}/target/tmp/extract", env::var("CARGO_MANIFEST_DIR")?);
+    if fs::exists(&dest)? {
+        fs::remove_dir_all(&dest)?;
+    }
+
+    fs.extract_to(&"/test_dir_1".into(), dest.as_ref())?;
+    assert_eq!(fs::read(format!("{}/1.txt", dest))?, b"No.1-1\n");
+    assert_eq!(fs::read(format!("{}/3.txt", dest))?, b"No.1-3\n");
+    assert_eq!(fs::read(format!("{}/test_dir_1_1/2.txt", dest))?, b"No.1-1-2\n");
+    assert_eq!(fs::read_dir(&dest)?.count(), 4);
+
+    // 更新日時も引き継ぐ
+    let entry = fs.resolve_chain(&"/test_dir_1/1.txt".into())?.pop().unwrap();
+    let modified = fs::metadata(format!("{}/1.txt", dest))?.modified()?;
+    assert_eq!(modified, entry.last_modify_date.to_system_time(&entry.last_modify_time));
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn extract_to_case_collision() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+    use fatfs::{FileSystem as FatFs, FsOptions};
+
+    // 大文字・小文字のみ異なる 2 つのディレクトリ (BAR の名前を FOO に書き換える)
+    let img_path = init_fat16()?;
+    {
+        let fatfs = FatFs::new(OpenOptions::new().read(true).write(true).open(&img_path)?, FsOptions::new())?;
+        let root_dir = fatfs.root_dir();
+        root_dir.create_dir("Foo")?.create_file("a.txt")?.write_all(b"lower\n")?;
+        root_dir.create_dir("BAR")?.create_file("b.txt")?.write_all(b"upper\n")?;
+    }
+    let mut bytes = fs::read(&img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    patch_root_entry(&mut bytes, &fs, b"BAR        ", |entry| entry[0..11].copy_from_slice(b"FOO        "));
+    let fs = Fat16::from_bytes(bytes)?;
+
+    let dest = format!("{}/target/tmp/extract_case", env::var("CARGO_MANIFEST_DIR")?);
+    if fs::exists(&dest)? {
+        fs::remove_dir_all(&dest)?;
+    }
+
+    // 後から書き出す FOO は FOO_1 になり、その中身も FOO_1 の下に書き出される
+    fs.extract_to(&"/".into(), dest.as_ref())?;
+    assert_eq!(fs::read(format!("{}/Foo/a.txt", dest))?, b"lower\n");
+    assert_eq!(fs::read(format!("{}/FOO_1/b.txt", dest))?, b"upper\n");
+    assert_eq!(fs::read_dir(format!("{}/Foo", dest))?.count(), 1);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn import_from() -> Result<(), Box<dyn StdError>> {
+    use std::io::Read;
+    use std::time::{Duration, UNIX_EPOCH};
+    use fat16_test::Fat16;
+    use fatfs::{FileSystem as FatFs, FsOptions};
+
+    // ホスト側に小さなツリーを作る
+    let src = format!("{}/target/tmp/import", env::var("CARGO_MANIFEST_DIR")?);
+    if fs::exists(&src)? {
+        fs::remove_dir_all(&src)?;
+    }
+    fs::create_dir_all(format!("{}/Sub Directory", src))?;
+    fs::write(format!("{}/hello.txt", src), b"hello\n")?;
+    fs::write(format!("{}/Sub Directory/big file.bin", src), vec![0x5A; 5000])?;
+    let mtime = UNIX_EPOCH + Duration::from_secs(1710510358);
+    File::options().write(true).open(format!("{}/hello.txt", src))?.set_modified(mtime)?;
+
+    let img_path = init_fat16()?;
+    let mut fs = Fat16::new(img_path)?;
+    fs.create_dir(&"/imported".into())?;
+    fs.import_from(src.as_ref(), &"/imported".into())?;
+
+    // イメージファイルに書き出し、開き直して読み戻す
+    let out_path = format!("{}/target/tmp/imported.img", env::var("CARGO_MANIFEST_DIR")?);
+    fs.save(&out_path)?;
+    let fs = Fat16::new(&out_path)?;
+    assert_eq!(fs.read_file(&"/imported/hello.txt".into())?, b"hello\n");
+    assert_eq!(fs.read_file(&"/imported/Sub Directory/big file.bin".into())?, vec![0x5A; 5000]);
+    assert!(fs.is_dir(&"/imported/sub directory".into())?);
+
+    // 更新日時 (2024/03/15 13:45:58)
+    let entry = fs.resolve_chain(&"/imported/hello.txt".into())?.pop().unwrap();
+    assert_eq!(entry.last_modify_date.to_system_time(&entry.last_modify_time), mtime);
+
+    // fatfs クレートからも読める
+    let fatfs = FatFs::new(File::open(&out_path)?, FsOptions::new())?;
+    let mut content = String::new();
+    fatfs.root_dir().open_file("imported/hello.txt")?.read_to_string(&mut content)?;
+    assert_eq!(content, "hello\n");
+    let mut content = vec![];
+    fatfs.root_dir().open_file("imported/Sub Directory/big file.bin")?.read_to_end(&mut content)?;
+    assert_eq!(content, vec![0x5A; 5000]);
+    assert_eq!(fs.read_file(&"/test_dir_3/long_2.txt".into())?, vec![0x62; 3000]);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn to_bytes_round_trip() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{Fat16, FatVolume};
+
+    // 何も変更していなければ元のイメージと一致する
+    let img_path = init_fat16()?;
+    let bytes = fs::read(&img_path)?;
+    for image in [Fat16::from_bytes(bytes.clone())?.to_bytes()?, Fat16::open(&img_path)?.to_bytes()?] {
+        assert_eq!(image.len(), bytes.len());
+        assert_eq!(image.iter().zip(&bytes).position(|(a, b)| a != b), None);
+    }
+
+    // FAT12 は 12 ビットのエントリを詰め直して書き出す
+    let fat12 = format_with_files(fatfs::FatType::Fat12, 2 * 1024 * 1024)?;
+    let FatVolume::Fat12(mut fs) = FatVolume::from_bytes(fat12.clone())? else {
+        panic!("not detected as FAT12");
+    };
+    let image = fs.to_bytes()?;
+    assert_eq!(image.iter().zip(&fat12).position(|(a, b)| a != b), None);
+    fs.write_file(&"/1.txt".into(), &[0x31; 3000])?;
+    let FatVolume::Fat12(fs) = FatVolume::from_bytes(fs.to_bytes()?)? else {
+        panic!("not detected as FAT12");
+    };
+    assert_eq!(fs.read_file(&"/1.txt".into())?, vec![0x31; 3000]);
+    assert_eq!(fs.read_file(&"/dir/long.txt".into())?, vec![0x61; 5000]);
+
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[serial]
+#[test]
+fn serde_listing() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(img_path)?;
+
+    // ディレクトリの一覧
+    let listing = serde_json::to_value(fs.read_directory(&"/test_dir_1".into())?)?;
+    let file = listing.as_array().unwrap().iter().find(|e| e["name"] == "1.txt").unwrap();
+    assert_eq!(file["file_size"], 7);
+    assert!(file["last_modify_date"]["year"].is_u64());
+
+    // バイト列は 16 進文字列
+    let ebpb = serde_json::to_value(&fs.ebpb)?;
+    assert_eq!(ebpb["volume_label"], "4641543136494d47202020");
+    assert_eq!(ebpb["boot_code"].as_str().unwrap().len(), 448 * 2);
+
+    // 入れ子の一覧
+    let tree: serde_json::Value = serde_json::from_str(&fs.to_json_tree(&"/test_dir_1".into())?)?;
+    let sub_dir = tree.as_array().unwrap().iter().find(|e| e["name"] == "test_dir_1_1").unwrap();
+    assert_eq!(sub_dir["children"].as_array().unwrap().len(), 3);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn locate() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    let img_path = init_fat16()?;
+    let fs = Fat16::new(&img_path)?;
+    let image = fs::read(&img_path)?;
+
+    // データ領域の先頭 = 予約領域 + FAT 領域 + ルートディレクトリ領域
+    let bytes_per_sector = fs.bpb.bytes_per_sector as usize;
+    let data_offset = fs.bpb.reserved_sector_count as usize * bytes_per_sector
+        + fs.bpb.num_fats as usize * fs.bpb.sectors_per_fat as usize * bytes_per_sector
+        + fs.bpb.root_entry_count as usize * 32;
+    let bytes_per_cluster = bytes_per_sector * fs.bpb.sectors_per_cluster as usize;
+
+    let located = fs.locate(&"/1.txt".into())?;
+    assert_eq!(located, [(2, data_offset)]);
+    assert_eq!(&image[located[0].1..located[0].1 + 5], b"No.1\n");
+
+    // 複数クラスタにまたがるファイル
+    let located = fs.locate(&"/test_dir_3/long_1.txt".into())?;
+    assert_eq!(located.len(), 2);
+    for (cluster, offset) in &located {
+        assert_eq!(*offset, data_offset + (*cluster as usize - 2) * bytes_per_cluster);
+        assert_eq!(image[*offset], 0x61);
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn check_crosslinks() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{ChainError, Fat16, Fat16Error};
+
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    assert!(fs.check_crosslinks()?.is_empty());
+
+    // /2.txt の先頭クラスタを /1.txt と同じにする
+    patch_root_entry(&mut bytes, &fs, b"2       TXT", |entry| entry[26..28].copy_from_slice(&2u16.to_le_bytes()));
+
+    // long_1.txt (18 -> 19) の 2 番目を long_2.txt (20 -> 21) の 21 に付け替える
+    set_fat_entry(&mut bytes, &fs, 18, 21);
+
+    let fs = Fat16::from_bytes(bytes)?;
+    assert_eq!(fs.check_crosslinks()?, [
+        (2, vec!["/1.txt".to_string(), "/2.txt".to_string()]),
+        (21, vec!["/test_dir_3/long_1.txt".to_string(), "/test_dir_3/long_2.txt".to_string()]),
+    ]);
+
+    // long_3.txt (22 -> 23) の末尾を先頭に繋いで循環させても止まらなくならない
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    set_fat_entry(&mut bytes, &fs, 23, 22);
+    let fs = Fat16::from_bytes(bytes)?;
+    let err = fs.check_crosslinks().unwrap_err();
+    match err.downcast_ref::<Fat16Error>() {
+        Some(Fat16Error::BrokenChain { path, error }) => {
+            assert_eq!(path, "/test_dir_3/long_3.txt");
+            assert_eq!(*error, ChainError::Cycle(22));
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    // ディレクトリのチェーンが自身を指していても止まらなくならない
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let dir_cluster = fs.root_dir.iter().find(|e| e.name == "test_dir_3").unwrap().first_cluster as u16;
+    set_fat_entry(&mut bytes, &fs, dir_cluster, dir_cluster);
+    let fs = Fat16::from_bytes(bytes)?;
+    let err = fs.check_crosslinks().unwrap_err();
+    match err.downcast_ref::<Fat16Error>() {
+        Some(Fat16Error::BrokenChain { path, error }) => {
+            assert_eq!(path, "/test_dir_3");
+            assert_eq!(*error, ChainError::Cycle(dir_cluster));
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn find_lost_clusters() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{ChainError, Fat16, Fat16Error};
+
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    assert!(fs.find_lost_clusters()?.is_empty());
+
+    // どのエントリからも参照されないチェーン (100 -> 101) を作る
+    set_fat_entry(&mut bytes, &fs, 100, 101);
+    set_fat_entry(&mut bytes, &fs, 101, 0xFFFF);
+
+    let fs = Fat16::from_bytes(bytes)?;
+    assert_eq!(fs.find_lost_clusters()?, [100, 101]);
+
+    // 循環したチェーンはエラーとして報告する
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    set_fat_entry(&mut bytes, &fs, 23, 22);
+    let fs = Fat16::from_bytes(bytes)?;
+    let err = fs.find_lost_clusters().unwrap_err();
+    match err.downcast_ref::<Fat16Error>() {
+        Some(Fat16Error::BrokenChain { path, error }) => {
+            assert_eq!(path, "/test_dir_3/long_3.txt");
+            assert_eq!(*error, ChainError::Cycle(22));
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    // ディレクトリのチェーンが自身を指している場合も同様
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let dir_cluster = fs.root_dir.iter().find(|e| e.name == "test_dir_3").unwrap().first_cluster as u16;
+    set_fat_entry(&mut bytes, &fs, dir_cluster, dir_cluster);
+    let fs = Fat16::from_bytes(bytes)?;
+    let err = fs.find_lost_clusters().unwrap_err();
+    match err.downcast_ref::<Fat16Error>() {
+        Some(Fat16Error::BrokenChain { path, error }) => {
+            assert_eq!(path, "/test_dir_3");
+            assert_eq!(*error, ChainError::Cycle(dir_cluster));
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn large_sector_size() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::Fat16;
+
+    for bytes_per_sector in [1024, 2048, 4096] {
+        let img_path = init_fat16_with_sector_size(bytes_per_sector)?;
+        for fs in [Fat16::new(&img_path)?, Fat16::open(&img_path)?] {
+            assert_eq!(fs.bpb.bytes_per_sector, bytes_per_sector);
+            assert_eq!(fs.image_geometry().total_bytes, 128 * 1024 * 1024);
+
+            // 全ファイルを読み戻せる
+            assert_eq!(fs.all_files(&"/".into())?.len(), 15);
+            assert_eq!(fs.read_file(&"/1.txt".into())?, b"No.1\n");
+            assert_eq!(fs.read_file(&"/test_dir_1/test_dir_1_1/3.txt".into())?, b"No.1-1-3\n");
+            assert_eq!(fs.read_file(&"/test_dir_3/long_2.txt".into())?, vec![0x62; 3000]);
+            assert_eq!(fs.read_file_range(&"/test_dir_3/long_3.txt".into(), 2990, 20)?, vec![0x63; 10]);
+            fs.verify_all_chains()?;
+
+            // 物理位置がイメージの内容と一致する
+            let image = fs::read(&img_path)?;
+            let (_, offset) = fs.locate(&"/2.txt".into())?[0];
+            assert_eq!(&image[offset..offset + 5], b"No.2\n");
+        }
+    }
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn fat_entry() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{ClusterStatus, Fat16};
+
+    let img_path = init_fat16()?;
+    let mut bytes = fs::read(img_path)?;
+    let fs = Fat16::from_bytes(bytes.clone())?;
+    let table = &fs.alloc_table;
+
+    // 先頭のデータクラスタ (/1.txt) は 1 クラスタで終端
+    let entry = fs.root_dir.iter().find(|e| e.name == "1.txt").unwrap();
+    assert_eq!(entry.first_cluster, 2);
+    assert_eq!(table.entry(2), Some(0xFFFF));
+    assert_eq!(table.cluster_status(2), Some(ClusterStatus::EndOfChain));
+
+    // /test_dir_3/long_1.txt は 2 クラスタ
+    let entry = fs.read_directory(&"/test_dir_3".into())?.into_iter().find(|e| e.name == "long_1.txt").unwrap();
+    let first_cluster = entry.first_cluster as u16;
+    assert_eq!(table.cluster_status(first_cluster), Some(ClusterStatus::Next(first_cluster + 1)));
+    assert_eq!(table.cluster_status(1000), Some(ClusterStatus::Free));
+
+    // 不良クラスタ・予約値
+    set_fat_entry(&mut bytes, &fs, 1000, 0xFFF7);
+    set_fat_entry(&mut bytes, &fs, 1001, 0xFFF0);
+    let fs = Fat16::from_bytes(bytes)?;
+    assert_eq!(fs.alloc_table.entry(1000), Some(0xFFF7));
+    assert_eq!(fs.alloc_table.cluster_status(1000), Some(ClusterStatus::Bad));
+    assert_eq!(fs.alloc_table.cluster_status(1001), Some(ClusterStatus::Reserved));
+    assert_eq!(ClusterStatus::classify(0x0001, fat16_test::FatType::Fat16), ClusterStatus::Reserved);
+
+    Ok(())
+}
+
+#[serial]
+#[test]
+fn fat_volume() -> Result<(), Box<dyn StdError>> {
+    use fat16_test::{FatType, FatVolume};
+
+    // FAT16 イメージはクラスタ数から FAT16 と判定される
+    let img_path = init_fat16()?;
+    let fs = FatVolume::open(img_path)?;
+    assert_eq!(fs.fat_type(), FatType::Fat16);
+    assert_eq!(fs.read_file(&"/test_dir_1/test_dir_1_1/2.txt".into())?, b"No.1-1-2\n");
+
+    // FAT12 / FAT32 でフォーマットしたイメージも同じように読める
+    let cases = [
+        (fatfs::FatType::Fat12, FatType::Fat12, 2 * 1024 * 1024),
+        (fatfs::FatType::Fat32, FatType::Fat32, 64 * 1024 * 1024),
+    ];
+    for (format_type, expected, size) in cases {
+        let fs = FatVolume::from_bytes(format_with_files(format_type, size)?)?;
+        assert_eq!(fs.fat_type(), expected);
+        assert!(fs.root_dir().iter().any(|entry| entry.name == "1.txt"));
+        assert_eq!(fs.read_file(&"/1.txt".into())?, b"No.1\n");
+        assert_eq!(fs.read_file(&"/dir/long.txt".into())?, vec![0x61; 5000]);
+        assert_eq!(fs.read_directory(&"/dir".into())?.len(), 3);
+    }
+
+    Ok(())
+}
+
+// 指定した FAT の種類でフォーマットし、いくつかのファイルを書き込んだイメージ
+fn format_with_files(fat_type: fatfs::FatType, size: usize) -> Result<Vec<u8>, Box<dyn StdError>> {
+    use std::io::Cursor;
+    use fatfs::{format_volume, FileSystem as FatFs, FsOptions, FormatVolumeOptions};
+
+    let mut img = Cursor::new(vec![0u8; size]);
+    format_volume(&mut img, FormatVolumeOptions::new().fat_type(fat_type))?;
+    {
+        let fs = FatFs::new(&mut img, FsOptions::new())?;
+        fs.root_dir().create_file("1.txt")?.write_all(b"No.1\n")?;
+        let dir = fs.root_dir().create_dir("dir")?;
+        dir.create_file("long.txt")?.write_all(&[0x61; 5000])?;
+    }
+    Ok(img.into_inner())
+}
+
+// long_name の LFN エントリ群と short_name の SFN エントリを並べたバイト列
+fn lfn_records(long_name: &str, short_name: &[u8; 11]) -> Vec<u8> {
+    let checksum = short_name
+        .iter()
+        .fold(0u8, |sum, b| ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(*b));
+
+    let mut units = long_name.encode_utf16().collect::<Vec<_>>();
+    if units.len() % 13 != 0 {
+        units.push(0x0000);
+    }
+    while units.len() % 13 != 0 {
+        units.push(0xFFFF);
+    }
+
+    let count = units.len() / 13;
+    let offsets = [1, 3, 5, 7, 9, 14, 16, 18, 20, 22, 24, 28, 30];
+    let mut records = vec![];
+    for seq in (1..=count).rev() {
+        let mut record = [0u8; 32];
+        record[0] = seq as u8 | if seq == count { 0x40 } else { 0x00 };
+        record[11] = 0x0f;
+        record[13] = checksum;
+        for (unit, offset) in units[(seq - 1) * 13..seq * 13].iter().zip(offsets) {
+            record[offset..offset + 2].copy_from_slice(&unit.to_le_bytes());
+        }
+        records.extend(record);
+    }
+
+    let mut record = [0u8; 32];
+    record[0..11].copy_from_slice(short_name);
+    record[11] = 0x20;
+    records.extend(record);
+    records
+}
+
+// 1 つ目の FAT の cluster 番目のエントリを書き換える
+fn set_fat_entry(bytes: &mut [u8], fs: &fat16_test::Fat16, cluster: u16, value: u16) {
+    let offset = fs.bpb.reserved_sector_count as usize * fs.bpb.bytes_per_sector as usize + cluster as usize * 2;
+    bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+}
+
 fn init_fat16() -> Result<String, Box<dyn StdError>> {
+    init_fat16_with_sector_size(512)
+}
+
+fn init_fat16_with_sector_size(bytes_per_sector: u16) -> Result<String, Box<dyn StdError>> {
     use fatfs::{format_volume, FileSystem as FatFs, FsOptions, FatType, FormatVolumeOptions};
 
     const MB: usize = 1024 * 1024;
@@ -85,9 +3005,10 @@ fn init_fat16() -> Result<String, Box<dyn StdError>> {
 
     // FAT16 でフォーマット
     let fmt_size = 128 * MB;
+    img_file.set_len(fmt_size as u64)?;
     let fmt_opts = FormatVolumeOptions::new()
-        .bytes_per_sector(512)
-        .total_sectors((fmt_size / 512) as u32)
+        .bytes_per_sector(bytes_per_sector)
+        .total_sectors((fmt_size / bytes_per_sector as usize) as u32)
         .max_root_dir_entries(512)
         .fats(2)
         .fat_type(FatType::Fat16)